@@ -0,0 +1,237 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+};
+
+use crate::wrap;
+
+/// a piece of terminal output, either text or an escape sequence
+enum Token<'a> {
+    Text(&'a str),
+    /// the parameters of a `CSI ... m` select graphic rendition sequence
+    Sgr(&'a str),
+    /// any other escape sequence, it has no visible effect on a slide
+    Other,
+}
+
+/// splits `src` into text and escape sequences
+fn tokens(src: &str) -> impl Iterator<Item = Token<'_>> {
+    let mut rest = src;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let Some(after) = rest.strip_prefix('\x1b') else {
+            let end = rest.find('\x1b').unwrap_or(rest.len());
+            let (text, tail) = rest.split_at(end);
+            rest = tail;
+            return Some(Token::Text(text));
+        };
+        if let Some(csi) = after.strip_prefix('[') {
+            let end = csi
+                .find(|c: char| ('\x40'..='\x7e').contains(&c))
+                .unwrap_or(csi.len());
+            let (params, tail) = csi.split_at(end);
+            let final_byte = tail.chars().next();
+            rest = &tail[final_byte.map_or(0, char::len_utf8)..];
+            return Some(match final_byte {
+                Some('m') => Token::Sgr(params),
+                _ => Token::Other,
+            });
+        }
+        if let Some(osc) = after.strip_prefix(']') {
+            // ends with a bell or `ESC \`
+            let end = osc.find(['\x07', '\x1b']).unwrap_or(osc.len());
+            let tail = &osc[end..];
+            rest = tail
+                .strip_prefix('\x07')
+                .or_else(|| tail.strip_prefix("\x1b\\"))
+                .unwrap_or(tail);
+            return Some(Token::Other);
+        }
+        rest = after
+            .char_indices()
+            .nth(1)
+            .map_or("", |(idx, _)| &after[idx..]);
+        Some(Token::Other)
+    })
+}
+
+/// `src` with its escape sequences removed, as it shows up on a terminal
+pub(crate) fn strip(src: &str) -> String {
+    tokens(src)
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `src` with its tabs expanded to stops every `width` columns, the escape
+/// sequences are kept and take no columns
+pub(crate) fn expand_tabs(src: &str, width: usize) -> String {
+    if !src.contains('\t') {
+        return src.into();
+    }
+    let mut out = String::with_capacity(src.len());
+    let mut column = 0;
+    let mut copied = 0;
+    for token in tokens(src) {
+        if let Token::Text(text) = token {
+            // the escapes between two pieces of text are copied as they are
+            let start = text.as_ptr() as usize - src.as_ptr() as usize;
+            out.push_str(&src[copied..start]);
+            column = wrap::push_expanded(&mut out, text, width, column);
+            copied = start + text.len();
+        }
+    }
+    out.push_str(&src[copied..]);
+    out
+}
+
+/// terminal output styled by its color and attribute escapes, others are dropped
+pub(crate) fn text(src: &str) -> Text<'static> {
+    let mut lines = vec![Line::default()];
+    let mut style = Style::default();
+    for token in tokens(src) {
+        match token {
+            Token::Text(text) => {
+                for (idx, piece) in text.split('\n').enumerate() {
+                    if idx > 0 {
+                        lines.push(Line::default());
+                    }
+                    let piece = piece.trim_end_matches('\r');
+                    if !piece.is_empty() {
+                        let line = lines.last_mut().unwrap();
+                        line.spans.push(Span::styled(piece.to_owned(), style));
+                    }
+                }
+            }
+            Token::Sgr(params) => style = sgr(style, params),
+            Token::Other => {}
+        }
+    }
+    // like `str::lines`, a trailing newline doesn't start another line
+    if src.ends_with('\n') {
+        lines.pop();
+    }
+    Text::from(lines)
+}
+
+/// applies the select graphic rendition `params` like `1;38;5;208` to `style`
+fn sgr(mut style: Style, params: &str) -> Style {
+    let mut codes = params
+        .split([';', ':'])
+        .map(|code| code.parse::<u8>().unwrap_or(0));
+    while let Some(code) = codes.next() {
+        style = match code {
+            0 => Style::default(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            25 => style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(Color::Indexed(code - 30)),
+            40..=47 => style.bg(Color::Indexed(code - 40)),
+            90..=97 => style.fg(Color::Indexed(code - 90 + 8)),
+            100..=107 => style.bg(Color::Indexed(code - 100 + 8)),
+            38 => match extended(&mut codes) {
+                Some(color) => style.fg(color),
+                None => style,
+            },
+            48 => match extended(&mut codes) {
+                Some(color) => style.bg(color),
+                None => style,
+            },
+            39 => Style { fg: None, ..style },
+            49 => Style { bg: None, ..style },
+            _ => style,
+        };
+    }
+    style
+}
+
+/// the color after a `38` or `48`, either `5;n` from the palette or `2;r;g;b`
+fn extended(codes: &mut impl Iterator<Item = u8>) -> Option<Color> {
+    match codes.next()? {
+        5 => Some(Color::Indexed(codes.next()?)),
+        2 => Some(Color::Rgb(codes.next()?, codes.next()?, codes.next()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_take_no_columns() {
+        let src = "\x1b[1;31mab\x1b[0m\tc\n\x1b[32m\t\x1b[0md";
+        assert_eq!(
+            expand_tabs(src, 4),
+            "\x1b[1;31mab\x1b[0m  c\n\x1b[32m    \x1b[0md"
+        );
+        assert_eq!(strip(&expand_tabs(src, 4)), "ab  c\n    d");
+    }
+
+    #[test]
+    fn strips_escapes() {
+        let src = "\x1b[1;31merror\x1b[0m: \x1b]8;;https://x.org\x1b\\link\x1b]8;;\x07 \x1b[2Kdone";
+        assert_eq!(strip(src), "error: link done");
+    }
+
+    #[test]
+    fn applies_attributes_and_colors() {
+        let style = sgr(Style::default(), "1;4;31;42");
+        assert_eq!(
+            style,
+            Style::default()
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+                .fg(Color::Indexed(1))
+                .bg(Color::Indexed(2))
+        );
+        let reset = sgr(style, "22;24;39;49");
+        assert_eq!((reset.fg, reset.bg), (None, None));
+        assert!(!reset
+            .add_modifier
+            .intersects(Modifier::BOLD | Modifier::UNDERLINED));
+        assert_eq!(sgr(style, "0"), Style::default());
+        assert_eq!(sgr(Style::default(), "91").fg, Some(Color::Indexed(9)));
+    }
+
+    #[test]
+    fn applies_extended_colors() {
+        assert_eq!(
+            sgr(Style::default(), "38;5;208").fg,
+            Some(Color::Indexed(208))
+        );
+        assert_eq!(
+            sgr(Style::default(), "48;2;1;2;3").bg,
+            Some(Color::Rgb(1, 2, 3))
+        );
+        assert_eq!(
+            sgr(Style::default(), "38:5:208").fg,
+            Some(Color::Indexed(208))
+        );
+        // cut short, the color is left alone
+        assert_eq!(sgr(Style::default(), "38;2;1").fg, None);
+    }
+
+    #[test]
+    fn styles_lines() {
+        let text = text("plain \x1b[32mgreen\nstill green\x1b[m\n");
+        assert_eq!(text.lines.len(), 2);
+        assert_eq!(text.lines[0].spans[0].content, "plain ");
+        assert_eq!(text.lines[0].spans[1].content, "green");
+        assert_eq!(text.lines[0].spans[1].style.fg, Some(Color::Indexed(2)));
+        assert_eq!(text.lines[1].spans[0].style.fg, Some(Color::Indexed(2)));
+    }
+}
@@ -0,0 +1,895 @@
+use crossterm::{
+    cursor,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+        MouseButton, MouseEventKind,
+    },
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{prelude::*, widgets::Block};
+use std::{
+    io::{self, Read, Stdout},
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    check, clipboard, export, font,
+    graphics::{self, ImageMode},
+    highlight::Background,
+    hyperlink,
+    keys::{Action, Keymap},
+    pdf, raster,
+    resume::Positions,
+    slide::{
+        mkslides, mkslides_from_str, overview_columns, render_command, render_help,
+        render_offscreen, render_overview, render_slide, render_status, render_zoomed, slide_area,
+        HtmlMode, Margin, ParseOptions, RenderOptions, Slides, TitleBar, TitleContent,
+        VerticalAlign,
+    },
+    theme, transition,
+    watch::Watcher,
+};
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use time::{OffsetDateTime, UtcOffset};
+
+/// very early stage cli slideshows
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Args {
+    /// markdown files to render as one deck, read from stdin if omitted
+    files: Vec<String>,
+    /// line that separates two slides
+    #[arg(long, default_value = "---")]
+    separator: String,
+    /// add a contents slide listing every slide's heading after the title slide
+    #[arg(long)]
+    toc: bool,
+    /// give the contents slide a number in the slide counter
+    #[arg(long, requires = "toc")]
+    toc_counted: bool,
+    /// curly quotes, `--` and `---` as en and em dashes and `...` as an ellipsis in text
+    #[arg(long)]
+    smart: bool,
+    /// columns between tab stops in code blocks
+    #[arg(long, value_name = "N", default_value_t = 4)]
+    tab_width: usize,
+    /// what to do with raw html in the markdown
+    #[arg(long, value_enum, value_name = "MODE", default_value_t)]
+    html: HtmlMode,
+    /// reload the slides when the file changes
+    #[arg(long, requires = "files")]
+    watch: bool,
+    /// start from the slide shown when the deck was last closed, and remember it on quit
+    #[arg(long, requires = "files")]
+    resume: bool,
+    /// with --resume, start from the first slide anyway
+    #[arg(long, requires = "resume")]
+    restart: bool,
+    /// write the deck to a standalone html file instead of presenting it
+    #[arg(long, value_name = "OUT")]
+    export_html: Option<PathBuf>,
+    /// write the deck back out as normalized markdown instead of presenting it
+    #[arg(long, value_name = "OUT")]
+    export_txt: Option<PathBuf>,
+    /// render every slide to a png image in this directory instead of presenting them
+    #[arg(long, value_name = "DIR")]
+    export_png: Option<PathBuf>,
+    /// write the deck to a pdf with one page per slide instead of presenting it
+    #[arg(long, value_name = "OUT")]
+    export_pdf: Option<PathBuf>,
+    /// page size of --export-pdf
+    #[arg(long, value_enum, default_value_t)]
+    pdf_page_size: pdf::PageSize,
+    /// monospace truetype font used for --export-png and --export-pdf,
+    /// an installed one is picked if omitted
+    #[arg(long, value_name = "FONT")]
+    png_font: Option<PathBuf>,
+    /// print the index and name of every slide instead of presenting them
+    #[arg(long)]
+    list: bool,
+    /// print the slides and the kinds of their items as json instead of presenting them
+    #[arg(long)]
+    json: bool,
+    /// report problems with the deck instead of presenting it, failing on errors
+    #[arg(long)]
+    check: bool,
+    /// show the next slide, speaker notes and a timer beside the current slide
+    #[arg(long)]
+    presenter: bool,
+    /// wrap code lines that are too wide instead of cutting them off
+    #[arg(long)]
+    code_wrap: bool,
+    /// show line numbers next to every code block
+    #[arg(long)]
+    code_line_numbers: bool,
+    /// draw qr codes one column per module, narrower but squashed
+    #[arg(long)]
+    qr_narrow: bool,
+    /// reveal the bullets of a slide one at a time
+    #[arg(long)]
+    incremental: bool,
+    /// go from the last slide to the first and back instead of stopping
+    #[arg(long)]
+    wrap: bool,
+    /// animate moving between slides, instant if omitted
+    #[arg(long, value_enum, value_name = "KIND", num_args = 0..=1, default_missing_value = "slide")]
+    transitions: Option<transition::Transition>,
+    /// advance to the next slide after this many seconds without input
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    auto: Option<u64>,
+    /// with --auto, start over from the first slide after the last one
+    #[arg(long = "loop", requires = "auto")]
+    loop_deck: bool,
+    /// start the `<!-- run: command -->` of a slide when it is shown
+    #[arg(long)]
+    allow_exec: bool,
+    /// where slide content is placed vertically
+    #[arg(long, value_enum, default_value_t)]
+    vertical_align: VerticalAlign,
+    /// render `$...$` in text as math, otherwise dollar signs are left alone
+    #[arg(long)]
+    math: bool,
+    /// columns left empty on each side of the content, and optionally the rows above the title bar
+    #[arg(long, value_name = "COLUMNS[,ROWS]", default_value = "4,1")]
+    margin: Margin,
+    /// show the local time in the top-left corner
+    #[arg(long)]
+    clock: bool,
+    /// highlight code for a light terminal background, detected from COLORFGBG if omitted
+    #[arg(long, conflicts_with = "theme_dark")]
+    theme_light: bool,
+    /// highlight code for a dark terminal background
+    #[arg(long)]
+    theme_dark: bool,
+    /// don't draw the title bar
+    #[arg(long)]
+    no_title: bool,
+    /// where the title bar is drawn
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHERE",
+        default_value_t,
+        conflicts_with = "no_title"
+    )]
+    title_position: TitleBar,
+    /// what the title bar shows
+    #[arg(long, value_enum, value_name = "WHAT", default_value_t)]
+    title_content: TitleContent,
+    /// scroll through slides too tall for the terminal with j and k instead of splitting them into pages
+    #[arg(long)]
+    scroll: bool,
+    /// how images are drawn, `auto` uses a graphics protocol where the terminal has one
+    #[arg(long, value_enum, value_name = "MODE", default_value_t)]
+    image_mode: ImageMode,
+}
+
+/// the command line interface, what the `mkslides` binary runs
+pub fn cli() -> Result<()> {
+    let args = Args::parse();
+    let opts = ParseOptions {
+        separator: args.separator,
+        html: args.html,
+        tab_width: args.tab_width,
+        smart: args.smart,
+        toc: args.toc,
+        toc_counted: args.toc_counted,
+        math: args.math,
+    };
+    // stdin can't be read twice, --check needs the markdown again
+    let mut stdin = None;
+    let mut slides = match args.files.as_slice() {
+        [] => {
+            let mut md_slides = String::new();
+            io::stdin().read_to_string(&mut md_slides)?;
+            let slides = mkslides_from_str(&md_slides, "<stdin>", ".", &opts)?;
+            stdin = Some(md_slides);
+            slides
+        }
+        files => mkslides(files, &opts)?,
+    };
+    if args.list {
+        for (idx, slide) in slides.slides().iter().enumerate() {
+            println!(
+                "{:>3}  {}",
+                idx + 1,
+                slide.name().as_deref().unwrap_or("(untitled)")
+            );
+        }
+        return Ok(());
+    }
+    if args.json {
+        println!("{}", export::to_json(&slides)?);
+        return Ok(());
+    }
+    if let Some(out) = &args.export_html {
+        std::fs::write(out, export::to_html(&slides))?;
+        return Ok(());
+    }
+    if let Some(out) = &args.export_txt {
+        std::fs::write(out, export::to_markdown(&slides))?;
+        return Ok(());
+    }
+    let render_opts = RenderOptions {
+        vertical_align: args.vertical_align,
+        theme: theme::Theme::load()?,
+        presenter: args.presenter,
+        code_wrap: args.code_wrap,
+        code_line_numbers: args.code_line_numbers,
+        qr_narrow: args.qr_narrow,
+        transition: args.transitions,
+        margin: args.margin,
+        // the local offset can only be looked up safely before any other thread starts
+        clock: args
+            .clock
+            .then(|| UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC)),
+        code_background: match (args.theme_light, args.theme_dark) {
+            (true, _) => Background::Light,
+            (_, true) => Background::Dark,
+            // exported images have a dark background whatever the terminal has
+            _ if args.export_png.is_some() || args.export_pdf.is_some() => Background::Dark,
+            _ => Background::detect(),
+        },
+        image_mode: match args.image_mode {
+            // there's no terminal to show protocol images in exported images
+            ImageMode::Auto if args.export_png.is_some() || args.export_pdf.is_some() => {
+                ImageMode::Blocks
+            }
+            mode => mode,
+        },
+        title_bar: if args.no_title {
+            TitleBar::Hidden
+        } else {
+            args.title_position
+        },
+        title_content: args.title_content,
+        scroll: args.scroll,
+    };
+    if args.check {
+        let files = match stdin {
+            Some(src) => vec![("<stdin>".into(), src)],
+            None => args
+                .files
+                .iter()
+                .map(|path| Ok((path.clone(), std::fs::read_to_string(path)?)))
+                .collect::<io::Result<Vec<_>>>()?,
+        };
+        // included files are checked on their own so problems point into them
+        let includes = slides
+            .includes()
+            .iter()
+            .map(|path| Ok((path.display().to_string(), std::fs::read_to_string(path)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+        let sources = [files, includes].concat();
+        let problems = check::check(&mut slides, &render_opts, &sources, &opts.separator);
+        for problem in &problems {
+            let level = if problem.error { "error" } else { "warning" };
+            eprintln!("{level}: {}", problem.msg);
+        }
+        let errors = problems.iter().filter(|problem| problem.error).count();
+        if errors > 0 {
+            bail!("found {errors} error(s) in the deck");
+        }
+        return Ok(());
+    }
+    if let Some(dir) = &args.export_png {
+        let font = font::Font::load(args.png_font.as_deref())?;
+        return raster::export_png(&mut slides, &render_opts, &font, dir);
+    }
+    if let Some(out) = &args.export_pdf {
+        let font = font::Font::load(args.png_font.as_deref())?;
+        return pdf::export_pdf(&mut slides, &render_opts, &font, args.pdf_page_size, out);
+    }
+    let keymap = Keymap::load()?;
+    let watcher = args
+        .watch
+        .then(|| Watcher::new(args.files.clone(), opts, &slides));
+    let positions = args.resume.then(Positions::load);
+    let mut terminal = TerminalGuard::new()?;
+    slides.set_incremental(args.incremental);
+    slides.set_wrap(args.wrap);
+    let saved = positions.as_ref().filter(|_| !args.restart);
+    if let Some(idx) = saved.and_then(|positions| positions.get(&args.files)) {
+        slides.goto(idx);
+    }
+    let autoplay = args.auto.map(|secs| Autoplay {
+        every: Duration::from_secs(secs),
+        looping: args.loop_deck,
+    });
+    let last = present(
+        slides,
+        watcher,
+        &render_opts,
+        &keymap,
+        autoplay,
+        args.allow_exec,
+        &mut terminal,
+    )?;
+    match positions {
+        Some(positions) => positions.save(&args.files, last),
+        None => Ok(()),
+    }
+}
+
+/// presents `slides` in the terminal until the viewer quits, with the default
+/// options and the user's theme and keymap
+pub fn run(slides: Slides) -> Result<()> {
+    let render_opts = RenderOptions {
+        theme: theme::Theme::load()?,
+        ..RenderOptions::default()
+    };
+    let keymap = Keymap::load()?;
+    let mut terminal = TerminalGuard::new()?;
+    present(
+        slides,
+        None,
+        &render_opts,
+        &keymap,
+        None,
+        false,
+        &mut terminal,
+    )?;
+    Ok(())
+}
+
+/// owns the terminal and puts it back into cooked mode when dropped
+struct TerminalGuard(Terminal<CrosstermBackend<Stdout>>);
+
+impl TerminalGuard {
+    fn new() -> Result<Self> {
+        // restore before the default hook prints, so the panic message is readable
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = restore_terminal();
+            hook(info);
+        }));
+        let mut stdout = io::stdout();
+        enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        Ok(Self(Terminal::new(CrosstermBackend::new(stdout))?))
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore_terminal();
+    }
+}
+
+impl Deref for TerminalGuard {
+    type Target = Terminal<CrosstermBackend<Stdout>>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+fn restore_terminal() -> Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        DisableMouseCapture,
+        LeaveAlternateScreen,
+        cursor::Show
+    )?;
+    Ok(())
+}
+
+/// how long a status message stays on screen
+const STATUS_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// how often the file being watched and running hooks are checked on
+const POLL: Duration = Duration::from_millis(200);
+
+/// longest wait for input when nothing else can change the screen
+const IDLE_POLL: Duration = Duration::from_secs(1);
+
+/// copies the code of the current slide, or only the zoomed block, and says how it went
+fn copy_code(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    slides: &Slides,
+    zoom: Option<usize>,
+) -> String {
+    let Some(code) = slides.current_code(zoom) else {
+        return "nothing to copy".into();
+    };
+    // osc 52 gets no answer, whether there's a clipboard to copy to is up to the terminal
+    match clipboard::copy(terminal.backend_mut(), &code) {
+        Ok(()) => format!(
+            "asked the terminal to copy {} line(s) of code",
+            code.lines().count()
+        ),
+        Err(e) => format!("could not copy the code: {e}"),
+    }
+}
+
+/// starts `cmd` in a shell from `dir`, its output would garble the slides so it's dropped
+fn start_hook(cmd: &str, dir: &Path) -> io::Result<Child> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    Command::new(shell)
+        .args([flag, cmd])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+}
+
+/// moving through the deck on its own, for kiosk displays
+struct Autoplay {
+    every: Duration,
+    /// go back to the first slide after the last one instead of stopping
+    looping: bool,
+}
+
+/// runs the slideshow until the viewer quits, returns the slide they were on
+fn present(
+    mut slides: Slides,
+    mut watcher: Option<Watcher>,
+    render_opts: &RenderOptions,
+    keymap: &Keymap,
+    autoplay: Option<Autoplay>,
+    allow_exec: bool,
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+) -> Result<usize> {
+    // digits typed so far for a jump, applied on Enter
+    let mut goto_buf = String::new();
+    let mut shown_idx = slides.current_idx();
+    let mut status: Option<(String, Instant)> = None;
+    let mut started = Instant::now();
+    let mut show_timer = true;
+    let mut show_help = false;
+    // solid color covering the slide while the screen is blanked
+    let mut blank: Option<Color> = None;
+    // slide id being typed after `:`, or search text after `/`
+    let mut command: Option<(char, String)> = None;
+    let mut search: Option<String> = None;
+    // slide picked in the overview grid while it's open
+    let mut overview: Option<usize> = None;
+    // item of the current slide shown on its own in focus mode
+    let mut zoom: Option<usize> = None;
+    // the slide the zoomed item is on
+    let mut zoomed_on = usize::MAX;
+    // terminal size the deck was last split into pages for
+    let mut paged_size = Rect::default();
+    // what was on screen last, a transition starts from it
+    let mut last_frame: Option<Buffer> = None;
+    // set when stepping to the next (`true`) or previous slide
+    let mut moved: Option<bool> = None;
+    // last key press, click or automatic advance
+    let mut idle_since = Instant::now();
+    // slide whose run hook was started last, going to another page of it doesn't start it again
+    let mut hooked: Option<usize> = None;
+    let mut hooks: Vec<(String, Child)> = vec![];
+    // something changed since the last frame, so the screen has to be drawn again
+    let mut dirty = true;
+    // whole seconds on the timer in the last frame
+    let mut drawn_secs: Option<u64> = None;
+    // minute on the clock in the last frame
+    let mut drawn_minute: Option<u8> = None;
+    loop {
+        if let Some(reloaded) = watcher.as_mut().and_then(Watcher::poll) {
+            match reloaded {
+                Ok(mut reloaded) => {
+                    reloaded.set_incremental(slides.is_incremental());
+                    reloaded.set_wrap(slides.is_wrap());
+                    reloaded.goto(slides.source_idx());
+                    slides = reloaded;
+                    paged_size = Rect::default();
+                    // force images to be redrawn from the new deck
+                    shown_idx = usize::MAX;
+                    status = None;
+                    zoom = None;
+                }
+                Err(e) => status = Some((format!("reload failed: {e}"), Instant::now())),
+            }
+            dirty = true;
+        }
+        let busy = command.is_some() || overview.is_some() || zoom.is_some() || show_help;
+        if let Some(autoplay) = &autoplay {
+            if !busy && idle_since.elapsed() >= autoplay.every {
+                idle_since = Instant::now();
+                if !slides.at_end() {
+                    moved = Some(true);
+                    slides.next();
+                } else if autoplay.looping {
+                    moved = Some(true);
+                    slides.first();
+                }
+            }
+        }
+        if status
+            .as_ref()
+            .is_some_and(|(_, at)| at.elapsed() >= STATUS_TIMEOUT)
+        {
+            status = None;
+            dirty = true;
+        }
+        let size = terminal.size()?;
+        if size != paged_size {
+            paged_size = size;
+            if !render_opts.scroll {
+                slides.paginate(slide_area(size, render_opts), render_opts);
+                // the zoomed item may have moved to another page
+                zoom = None;
+            }
+        }
+        // clicks and scrolling move on without leaving the zoom
+        if slides.current_idx() != zoomed_on {
+            zoom = None;
+        }
+        if hooked != Some(slides.source_idx()) {
+            hooked = Some(slides.source_idx());
+            match slides.current_run() {
+                Some(cmd) if allow_exec => match start_hook(cmd, slides.base_dir()) {
+                    Ok(child) => hooks.push((cmd.into(), child)),
+                    Err(e) => {
+                        status = Some((format!("could not run '{cmd}': {e}"), Instant::now()))
+                    }
+                },
+                Some(cmd) => {
+                    let msg = format!("not running '{cmd}' without --allow-exec");
+                    status = Some((msg, Instant::now()));
+                }
+                None => {}
+            }
+        }
+        // hooks run in the background, a failure shows up once they exit
+        hooks.retain_mut(|(cmd, child)| match child.try_wait() {
+            Ok(None) => true,
+            Ok(Some(exit)) if exit.success() => false,
+            Ok(Some(exit)) => {
+                status = Some((format!("'{cmd}' failed with {exit}"), Instant::now()));
+                dirty = true;
+                false
+            }
+            Err(e) => {
+                status = Some((format!("could not wait for '{cmd}': {e}"), Instant::now()));
+                dirty = true;
+                false
+            }
+        });
+        let changed = shown_idx != slides.current_idx();
+        if changed {
+            dirty = true;
+            shown_idx = slides.current_idx();
+            // the buffer diff can't see images, so wipe them explicitly
+            if graphics::clear(terminal.backend_mut())? {
+                terminal.clear()?;
+            }
+        }
+        let elapsed = show_timer.then(|| started.elapsed());
+        let secs = elapsed.map(|elapsed| elapsed.as_secs());
+        dirty |= secs != drawn_secs;
+        let now = render_opts
+            .clock
+            .map(|offset| OffsetDateTime::now_utc().to_offset(offset).time());
+        let minute = now.map(|now| now.minute());
+        dirty |= minute != drawn_minute;
+        let step = moved.take().filter(|_| changed && !busy && blank.is_none());
+        if dirty {
+            if let (Some(kind), Some(forward), Some(from)) =
+                (render_opts.transition, step, &last_frame)
+            {
+                let to = render_offscreen(&slides, render_opts, elapsed, now, from.area)?;
+                transition::play(terminal, kind, from, &to, forward)?;
+            }
+            let render = render_slide(&slides, render_opts, elapsed, now)?;
+            let frame = terminal.draw(|frame| {
+                match (blank, overview) {
+                    (Some(color), _) => frame.render_widget(Block::new().bg(color), frame.size()),
+                    (None, Some(selected)) => {
+                        render_overview(frame, &slides, selected, &render_opts.theme)
+                    }
+                    (None, None) => match zoom {
+                        Some(item) => render_zoomed(frame, &slides, item, render_opts),
+                        None => render(frame),
+                    },
+                }
+                if let Some((msg, _)) = &status {
+                    render_status(frame, msg);
+                }
+                if let Some((prompt, cmd)) = &command {
+                    render_command(frame, *prompt, cmd);
+                }
+                if show_help {
+                    render_help(frame, &keymap.help());
+                }
+            })?;
+            last_frame = Some(frame.buffer.clone());
+            if show_help || overview.is_some() || blank.is_some() {
+                // images and links are written over the frame and would cover the help
+                hyperlink::discard();
+                if graphics::clear(terminal.backend_mut())? {
+                    terminal.clear()?;
+                }
+            } else {
+                graphics::flush(terminal.backend_mut())?;
+                hyperlink::flush(terminal.backend_mut())?;
+            }
+            dirty = false;
+            drawn_secs = secs;
+            drawn_minute = minute;
+        }
+        // sleep until something on screen is due to change, input wakes it up earlier
+        let mut wait = if watcher.is_some() || !hooks.is_empty() {
+            POLL
+        } else {
+            IDLE_POLL
+        };
+        if show_timer {
+            let subsec = Duration::from_nanos(started.elapsed().subsec_nanos().into());
+            wait = wait.min(Duration::from_secs(1) - subsec);
+        }
+        if let Some(now) = now {
+            let into_minute = Duration::new(now.second().into(), now.nanosecond());
+            wait = wait.min(Duration::from_secs(60).saturating_sub(into_minute));
+        }
+        if let Some((_, at)) = &status {
+            wait = wait.min(STATUS_TIMEOUT.saturating_sub(at.elapsed()));
+        }
+        if let Some(autoplay) = autoplay.as_ref().filter(|_| !busy) {
+            wait = wait.min(autoplay.every.saturating_sub(idle_since.elapsed()));
+        }
+        if event::poll(wait)? {
+            let event = event::read()?;
+            let input = match &event {
+                Event::Key(key) => key.kind != KeyEventKind::Release,
+                Event::Mouse(mouse) => !matches!(
+                    mouse.kind,
+                    MouseEventKind::Moved | MouseEventKind::Up(_) | MouseEventKind::Drag(_)
+                ),
+                _ => false,
+            };
+            if input {
+                idle_since = Instant::now();
+                dirty = true;
+            }
+            // clicks and scrolling move between slides, which brings the slide back,
+            // the overview, zoom and prompts are only worked with keys
+            let clickable = command.is_none() && overview.is_none() && zoom.is_none();
+            if input && clickable && matches!(event, Event::Mouse(_)) {
+                blank = None;
+            }
+            match event {
+                // terminals without the kitty keyboard protocol only report presses,
+                // so act on those and ignore releases to avoid firing twice
+                Event::Key(key) if key.kind != KeyEventKind::Release && command.is_some() => {
+                    let (prompt, cmd) = command.as_mut().unwrap();
+                    match key.code {
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            break
+                        }
+                        KeyCode::Char(c) => cmd.push(c),
+                        KeyCode::Backspace => {
+                            cmd.pop();
+                        }
+                        KeyCode::Esc => command = None,
+                        KeyCode::Enter if *prompt == '/' => {
+                            if !cmd.is_empty() && !slides.search(cmd, true) {
+                                status = Some((format!("no match for '{cmd}'"), Instant::now()));
+                            }
+                            search = Some(std::mem::take(cmd)).filter(|cmd| !cmd.is_empty());
+                            command = None;
+                        }
+                        KeyCode::Enter => {
+                            let name = cmd.trim();
+                            if !slides.goto_name(name) {
+                                status =
+                                    Some((format!("no slide with id '{name}'"), Instant::now()));
+                            }
+                            command = None;
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Key(key) if key.kind != KeyEventKind::Release && overview.is_some() => {
+                    let selected = overview.as_mut().unwrap();
+                    let cols = overview_columns(size.width);
+                    let last = slides.slides().len() - 1;
+                    match (key.code, keymap.action(key.code)) {
+                        (KeyCode::Char('c'), _)
+                            if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                        {
+                            break
+                        }
+                        (KeyCode::Char('j') | KeyCode::Down, _) => {
+                            *selected = (*selected + cols).min(last)
+                        }
+                        (KeyCode::Char('k') | KeyCode::Up, _) => {
+                            *selected = selected.saturating_sub(cols)
+                        }
+                        (_, Some(Action::Next)) => *selected = (*selected + 1).min(last),
+                        (_, Some(Action::Prev)) => *selected = selected.saturating_sub(1),
+                        (KeyCode::Enter, _) => {
+                            slides.goto(*selected);
+                            overview = None;
+                        }
+                        (KeyCode::Esc, _) | (_, Some(Action::Overview | Action::Quit)) => {
+                            overview = None
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Key(key) if key.kind != KeyEventKind::Release && zoom.is_some() => {
+                    let selected = zoom.as_mut().unwrap();
+                    let zoomable = slides.zoomable();
+                    let Some(&last) = zoomable.last() else {
+                        zoom = None;
+                        shown_idx = usize::MAX;
+                        continue;
+                    };
+                    let pos = zoomable.iter().position(|idx| idx == selected).unwrap_or(0);
+                    match (key.code, keymap.action(key.code)) {
+                        (KeyCode::Char('c'), _)
+                            if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                        {
+                            break
+                        }
+                        (KeyCode::Char('j') | KeyCode::Down, _) => {
+                            *selected = zoomable.get(pos + 1).copied().unwrap_or(last)
+                        }
+                        (KeyCode::Char('k') | KeyCode::Up, _) => {
+                            *selected = zoomable[pos.saturating_sub(1)]
+                        }
+                        (_, Some(Action::CopyCode)) => {
+                            status = Some((copy_code(terminal, &slides, zoom), Instant::now()))
+                        }
+                        (KeyCode::Esc, _) | (_, Some(Action::Zoom | Action::Quit)) => zoom = None,
+                        _ => {}
+                    }
+                    // the images drawn for the old view have to go
+                    shown_idx = usize::MAX;
+                }
+                Event::Key(key) if key.kind != KeyEventKind::Release => match key.code {
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                    KeyCode::Esc if show_help => show_help = false,
+                    // the keymap goes first so digits and enter can be bound too
+                    code => match keymap.action(code) {
+                        Some(Action::Quit) => break,
+                        Some(Action::Next | Action::Prev | Action::First | Action::Last)
+                            if blank.is_some() =>
+                        {
+                            // the first step only takes the blank screen away
+                            blank = None;
+                        }
+                        Some(Action::Next) => {
+                            goto_buf.clear();
+                            moved = Some(true);
+                            slides.next()
+                        }
+                        Some(Action::Prev) => {
+                            goto_buf.clear();
+                            moved = Some(false);
+                            slides.prev()
+                        }
+                        Some(Action::First) => {
+                            goto_buf.clear();
+                            slides.first()
+                        }
+                        Some(Action::Last) => {
+                            goto_buf.clear();
+                            slides.last()
+                        }
+                        Some(Action::Goto) => {
+                            goto_buf.clear();
+                            command = Some((':', String::new()));
+                        }
+                        Some(Action::Search) => {
+                            goto_buf.clear();
+                            command = Some(('/', String::new()));
+                        }
+                        Some(action @ (Action::SearchNext | Action::SearchPrev)) => {
+                            goto_buf.clear();
+                            let forward = action == Action::SearchNext;
+                            match &search {
+                                Some(query) if !slides.search(query, forward) => {
+                                    status =
+                                        Some((format!("no match for '{query}'"), Instant::now()))
+                                }
+                                Some(_) => {}
+                                None => status = Some(("no search yet".into(), Instant::now())),
+                            }
+                        }
+                        Some(Action::CopyCode) => {
+                            status = Some((copy_code(terminal, &slides, None), Instant::now()))
+                        }
+                        Some(Action::ToggleTimer) => show_timer = !show_timer,
+                        Some(Action::ResetTimer) => started = Instant::now(),
+                        Some(action @ (Action::BlackScreen | Action::WhiteScreen)) => {
+                            let color = match action {
+                                Action::BlackScreen => Color::Black,
+                                _ => Color::White,
+                            };
+                            blank = (blank != Some(color)).then_some(color);
+                        }
+                        Some(action @ (Action::ScrollDown | Action::ScrollUp)) => {
+                            let down = action == Action::ScrollDown;
+                            if slides.scroll(down, slide_area(size, render_opts), render_opts) {
+                                // images move with the slide
+                                shown_idx = usize::MAX;
+                            }
+                        }
+                        Some(Action::Help) => show_help = !show_help,
+                        Some(Action::Overview) => {
+                            goto_buf.clear();
+                            overview = Some(slides.current_idx());
+                        }
+                        Some(Action::Zoom) => match slides.zoomable().first() {
+                            Some(&item) => {
+                                zoom = Some(item);
+                                zoomed_on = slides.current_idx();
+                                shown_idx = usize::MAX;
+                            }
+                            None => {
+                                let msg = "nothing to zoom into on this slide".into();
+                                status = Some((msg, Instant::now()));
+                            }
+                        },
+                        None => match code {
+                            KeyCode::Char(c @ '0'..='9') => goto_buf.push(c),
+                            KeyCode::Enter => {
+                                if let Ok(n) = goto_buf.parse::<usize>() {
+                                    blank = None;
+                                    slides.goto_number(n);
+                                }
+                                goto_buf.clear();
+                            }
+                            _ => {}
+                        },
+                    },
+                },
+                Event::Mouse(mouse) if clickable => match mouse.kind {
+                    // click the right half to go forward, the left half to go back
+                    MouseEventKind::Down(MouseButton::Left) if mouse.column >= size.width / 2 => {
+                        goto_buf.clear();
+                        moved = Some(true);
+                        slides.next()
+                    }
+                    MouseEventKind::Down(MouseButton::Left) | MouseEventKind::ScrollUp => {
+                        goto_buf.clear();
+                        moved = Some(false);
+                        slides.prev()
+                    }
+                    MouseEventKind::ScrollDown => {
+                        goto_buf.clear();
+                        moved = Some(true);
+                        slides.next()
+                    }
+                    _ => {}
+                },
+                Event::Resize(width, height) => {
+                    // lay the deck out again for the new size before the next poll,
+                    // the old frame and image positions are no good anymore
+                    terminal.resize(Rect::new(0, 0, width, height))?;
+                    paged_size = Rect::default();
+                    shown_idx = usize::MAX;
+                    last_frame = None;
+                    dirty = true;
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(slides.source_idx())
+}
@@ -0,0 +1,336 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::Read,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use flate2::read::ZlibDecoder;
+use ratatui::{
+    prelude::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+/// how an image is drawn with text when there's no graphics protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Art {
+    /// `▀` half blocks, two colored pixels per cell
+    Blocks,
+    /// braille dots, two by four per cell in one color
+    Braille,
+}
+
+/// a decoded picture, four bytes per pixel
+pub(crate) struct Picture {
+    width: usize,
+    height: usize,
+    rgba: Vec<[u8; 4]>,
+}
+
+thread_local! {
+    static DECODED: RefCell<HashMap<PathBuf, Option<Rc<Picture>>>> = RefCell::new(HashMap::new());
+}
+
+/// decodes the png at `path` once and keeps it for later frames,
+/// `None` if it isn't a png this can read
+pub(crate) fn load(path: &Path) -> Option<Rc<Picture>> {
+    DECODED.with(|decoded| {
+        decoded
+            .borrow_mut()
+            .entry(path.to_owned())
+            .or_insert_with(|| {
+                let data = std::fs::read(path).ok()?;
+                decode_png(&data).map(Rc::new)
+            })
+            .clone()
+    })
+}
+
+/// lines of text drawing `picture` into `area`, which should already have its aspect ratio
+pub(crate) fn lines(picture: &Picture, area: Rect, art: Art) -> Vec<Line<'static>> {
+    let (cols, rows) = (area.width as usize, area.height as usize);
+    match art {
+        Art::Blocks => {
+            let pixels = picture.resize(cols, rows * 2);
+            (0..rows)
+                .map(|y| {
+                    Line::from(
+                        (0..cols)
+                            .map(|x| {
+                                half_blocks(
+                                    pixels[y * 2 * cols + x],
+                                    pixels[(y * 2 + 1) * cols + x],
+                                )
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect()
+        }
+        Art::Braille => {
+            let (width, height) = (cols * 2, rows * 4);
+            let pixels = picture.resize(width, height);
+            let dots = dither(&pixels, width, height);
+            (0..rows)
+                .map(|y| {
+                    Line::from(
+                        (0..cols)
+                            .map(|x| braille_cell(&pixels, &dots, width, x * 2, y * 4))
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect()
+        }
+    }
+}
+
+/// one cell showing `top` over `bottom`, transparent pixels keep the background
+fn half_blocks(top: [u8; 4], bottom: [u8; 4]) -> Span<'static> {
+    let color = |[r, g, b, _]: [u8; 4]| Color::Rgb(r, g, b);
+    match (top[3] >= 128, bottom[3] >= 128) {
+        (true, true) => Span::styled("▀", Style::default().fg(color(top)).bg(color(bottom))),
+        (true, false) => Span::styled("▀", Style::default().fg(color(top))),
+        (false, true) => Span::styled("▄", Style::default().fg(color(bottom))),
+        (false, false) => Span::raw(" "),
+    }
+}
+
+/// the braille character for the two by four dots at `left`, `top`, colored
+/// with the average of the pixels that are lit
+fn braille_cell(
+    pixels: &[[u8; 4]],
+    dots: &[bool],
+    width: usize,
+    left: usize,
+    top: usize,
+) -> Span<'static> {
+    // bit of every dot, by column and then row
+    const BITS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+    let mut bits = 0;
+    let mut sum = [0u32; 3];
+    let mut lit = 0;
+    for (dx, column) in BITS.iter().enumerate() {
+        for (dy, bit) in column.iter().enumerate() {
+            let idx = (top + dy) * width + left + dx;
+            if dots[idx] {
+                bits |= bit;
+                lit += 1;
+                for (sum, channel) in sum.iter_mut().zip(pixels[idx]) {
+                    *sum += channel as u32;
+                }
+            }
+        }
+    }
+    if lit == 0 {
+        return Span::raw(" ");
+    }
+    let [r, g, b] = sum.map(|sum| (sum / lit) as u8);
+    let dots = char::from_u32(0x2800 + bits).unwrap_or(' ');
+    Span::styled(dots.to_string(), Style::default().fg(Color::Rgb(r, g, b)))
+}
+
+/// which pixels are bright enough to light their dot, with floyd-steinberg
+/// dithering so shades come out as patterns instead of flat areas
+fn dither(pixels: &[[u8; 4]], width: usize, height: usize) -> Vec<bool> {
+    let mut light = pixels
+        .iter()
+        .map(|&[r, g, b, a]| {
+            let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+            luma * a as f32 / 255.0
+        })
+        .collect::<Vec<_>>();
+    let mut dots = vec![false; light.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let idx = y * width + x;
+            let on = light[idx] >= 128.0;
+            dots[idx] = on;
+            let error = light[idx] - if on { 255.0 } else { 0.0 };
+            let mut spread = |dx: isize, dy: usize, share: f32| {
+                let nx = x as isize + dx;
+                if nx >= 0 && (nx as usize) < width && y + dy < height {
+                    light[(y + dy) * width + nx as usize] += error * share;
+                }
+            };
+            spread(1, 0, 7.0 / 16.0);
+            spread(-1, 1, 3.0 / 16.0);
+            spread(0, 1, 5.0 / 16.0);
+            spread(1, 1, 1.0 / 16.0);
+        }
+    }
+    dots
+}
+
+impl Picture {
+    /// scales to `width` by `height` pixels, each one the average of the area it covers
+    fn resize(&self, width: usize, height: usize) -> Vec<[u8; 4]> {
+        let mut out = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let (y0, y1) = span(y, height, self.height);
+            for x in 0..width {
+                let (x0, x1) = span(x, width, self.width);
+                let mut sum = [0u32; 4];
+                for sy in y0..y1 {
+                    for pixel in &self.rgba[sy * self.width + x0..sy * self.width + x1] {
+                        for (sum, channel) in sum.iter_mut().zip(pixel) {
+                            *sum += *channel as u32;
+                        }
+                    }
+                }
+                let count = ((y1 - y0) * (x1 - x0)) as u32;
+                out.push(sum.map(|sum| (sum / count) as u8));
+            }
+        }
+        out
+    }
+}
+
+/// source pixels covered by pixel `idx` out of `count`, at least one
+fn span(idx: usize, count: usize, source: usize) -> (usize, usize) {
+    let start = idx * source / count;
+    let end = ((idx + 1) * source / count).max(start + 1).min(source);
+    (start.min(end - 1), end)
+}
+
+/// reads a non-interlaced png of any color type and bit depth
+fn decode_png(data: &[u8]) -> Option<Picture> {
+    let mut rest = data.strip_prefix(b"\x89PNG\r\n\x1a\n")?;
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut compressed = vec![];
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes(rest[..4].try_into().ok()?) as usize;
+        let kind = &rest[4..8];
+        let body = rest.get(8..8 + len)?;
+        match kind {
+            b"IHDR" => header = Some(body),
+            b"PLTE" => palette = body,
+            b"tRNS" => transparency = body,
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = rest.get(12 + len..)?;
+    }
+    let header = header.filter(|header| header.len() >= 13)?;
+    let width = u32::from_be_bytes(header[..4].try_into().ok()?) as usize;
+    let height = u32::from_be_bytes(header[4..8].try_into().ok()?) as usize;
+    let (depth, color_type, interlace) = (header[8] as usize, header[9], header[12]);
+    let channels = match color_type {
+        0 | 3 => 1,
+        2 => 3,
+        4 => 2,
+        6 => 4,
+        _ => return None,
+    };
+    if interlace != 0 || width == 0 || height == 0 || ![1, 2, 4, 8, 16].contains(&depth) {
+        return None;
+    }
+    let mut raw = vec![];
+    ZlibDecoder::new(compressed.as_slice())
+        .read_to_end(&mut raw)
+        .ok()?;
+    let stride = (width * channels * depth).div_ceil(8);
+    let bpp = (channels * depth).div_ceil(8);
+    let mut rows = vec![vec![0u8; stride]; height];
+    for y in 0..height {
+        let line = raw.get(y * (stride + 1)..(y + 1) * (stride + 1))?;
+        let (filter, line) = (line[0], &line[1..]);
+        let (done, current) = rows.split_at_mut(y);
+        let prev = done.last().map(Vec::as_slice);
+        unfilter(filter, line, &mut current[0], prev, bpp)?;
+    }
+    let max = ((1u32 << depth) - 1) as f32;
+    let mut rgba = Vec::with_capacity(width * height);
+    for row in &rows {
+        for x in 0..width {
+            // samples scaled down to eight bits, or palette indices
+            let sample = |channel: usize| -> u8 {
+                let idx = x * channels + channel;
+                match depth {
+                    16 => row[idx * 2],
+                    8 => row[idx],
+                    _ => {
+                        let bit = idx * depth;
+                        let value = (row[bit / 8] >> (8 - depth - bit % 8)) & ((1 << depth) - 1);
+                        if color_type == 3 {
+                            value
+                        } else {
+                            (value as f32 * 255.0 / max) as u8
+                        }
+                    }
+                }
+            };
+            rgba.push(match color_type {
+                0 => {
+                    let v = sample(0);
+                    [v, v, v, 255]
+                }
+                2 => [sample(0), sample(1), sample(2), 255],
+                3 => {
+                    let idx = sample(0) as usize;
+                    let rgb = palette.get(idx * 3..idx * 3 + 3)?;
+                    let alpha = transparency.get(idx).copied().unwrap_or(255);
+                    [rgb[0], rgb[1], rgb[2], alpha]
+                }
+                4 => {
+                    let v = sample(0);
+                    [v, v, v, sample(1)]
+                }
+                _ => [sample(0), sample(1), sample(2), sample(3)],
+            });
+        }
+    }
+    Some(Picture {
+        width,
+        height,
+        rgba,
+    })
+}
+
+/// undoes the png filter of one scanline, `prev` is the line above once decoded
+fn unfilter(
+    filter: u8,
+    line: &[u8],
+    out: &mut [u8],
+    prev: Option<&[u8]>,
+    bpp: usize,
+) -> Option<()> {
+    for i in 0..line.len() {
+        let left = if i >= bpp { out[i - bpp] } else { 0 };
+        let up = prev.map_or(0, |prev| prev[i]);
+        let up_left = match prev {
+            Some(prev) if i >= bpp => prev[i - bpp],
+            _ => 0,
+        };
+        let predicted = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => return None,
+        };
+        out[i] = line[i].wrapping_add(predicted);
+    }
+    Some(())
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
@@ -0,0 +1,195 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use ratatui::style::Modifier;
+
+use crate::slide::{
+    deck, image_path, Bullet, CodeBlock, Frontmatter, ParseOptions, Run, Slide, SlideItem, Slides,
+};
+
+/// builds a deck in code instead of parsing markdown, into the same slides
+/// and items the parser makes, so both are drawn the same way
+#[derive(Debug, Default)]
+pub struct SlideBuilder {
+    meta: Frontmatter,
+    slides: Vec<Vec<SlideItem>>,
+    /// relative image paths start here
+    base_dir: PathBuf,
+}
+
+impl SlideBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// names the deck, otherwise its first heading does like in markdown
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.meta.title = Some(title.into());
+        self
+    }
+
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.meta.author = Some(author.into());
+        self
+    }
+
+    /// directory relative image paths are resolved against
+    pub fn base_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.base_dir = dir.as_ref().to_owned();
+        self
+    }
+
+    /// ends the current slide, items added after this go on a new one
+    pub fn slide(mut self) -> Self {
+        self.slides.push(vec![]);
+        self
+    }
+
+    pub fn heading(self, text: &str) -> Self {
+        self.item(SlideItem::Heading(1, runs(text)))
+    }
+
+    /// a heading below the top level, `level` 2 for `##` up to 6
+    pub fn subheading(self, level: u8, text: &str) -> Self {
+        self.item(SlideItem::Heading(level.clamp(2, 6), runs(text)))
+    }
+
+    pub fn paragraph(self, text: &str) -> Self {
+        self.item(SlideItem::Paragraph(runs(text)))
+    }
+
+    /// a bullet point, joining the list right before it if there is one
+    pub fn bullet(self, text: &str) -> Self {
+        self.list_item(None, text)
+    }
+
+    /// a numbered point, counting on from the list right before it
+    pub fn numbered(self, text: &str) -> Self {
+        let number = match self.items().and_then(|items| items.last()) {
+            Some(SlideItem::Bullets(bullets)) => bullets.last().and_then(|b| b.number),
+            _ => None,
+        };
+        self.list_item(Some(number.map_or(1, |n| n + 1)), text)
+    }
+
+    /// a code block, `info` takes the same options as after a fence, like `rust,linenos {2}`
+    pub fn code(self, info: &str, src: &str) -> Self {
+        // the parser keeps the newline before the closing fence
+        let src = if src.ends_with('\n') {
+            src.to_owned()
+        } else {
+            format!("{src}\n")
+        };
+        let mut code = CodeBlock::new(info, &src);
+        code.expand_tabs(ParseOptions::default().tab_width);
+        self.item(SlideItem::Code(code))
+    }
+
+    pub fn quote(self, text: &str) -> Self {
+        self.item(SlideItem::Quote(text.into()))
+    }
+
+    pub fn image(self, path: impl AsRef<Path>, alt: &str) -> Self {
+        let path = image_path(&self.base_dir, &path.as_ref().to_string_lossy());
+        self.item(SlideItem::Image {
+            path,
+            alt: alt.into(),
+        })
+    }
+
+    /// speaker notes, shown with `--presenter` but not on the slide
+    pub fn notes(self, text: &str) -> Self {
+        self.item(SlideItem::Notes(text.into()))
+    }
+
+    pub fn rule(self) -> Self {
+        self.item(SlideItem::Rule)
+    }
+
+    /// the deck, failing like the parser does if no slide has anything on it
+    pub fn finish(self) -> Result<Slides> {
+        let slides = self
+            .slides
+            .into_iter()
+            .filter(|items| !items.is_empty())
+            .map(Slide::new)
+            .collect();
+        deck(self.meta, slides, "slides", &self.base_dir)
+    }
+
+    fn items(&self) -> Option<&Vec<SlideItem>> {
+        self.slides.last()
+    }
+
+    fn item(mut self, item: SlideItem) -> Self {
+        match self.slides.last_mut() {
+            Some(items) => items.push(item),
+            None => self.slides.push(vec![item]),
+        }
+        self
+    }
+
+    fn list_item(mut self, number: Option<usize>, text: &str) -> Self {
+        let bullet = Bullet {
+            depth: 0,
+            number,
+            checked: None,
+            text: runs(text),
+            hidden: false,
+        };
+        match self.slides.last_mut().and_then(|items| items.last_mut()) {
+            Some(SlideItem::Bullets(bullets)) => bullets.push(bullet),
+            _ => self = self.item(SlideItem::Bullets(vec![bullet])),
+        }
+        self
+    }
+}
+
+/// plain text as the one run the parser would make of it
+fn runs(text: &str) -> Vec<Run> {
+    vec![Run {
+        text: text.into(),
+        modifier: Modifier::empty(),
+        link: None,
+        code: false,
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slide::{mkslides_from_str, render_slide_to_buffer};
+
+    #[test]
+    fn draws_like_markdown() {
+        let built = SlideBuilder::new()
+            .title("Talk")
+            .heading("Hello")
+            .paragraph("Some words")
+            .bullet("one")
+            .bullet("two")
+            .slide()
+            .subheading(2, "Steps")
+            .numbered("first")
+            .numbered("second")
+            .code("rust {1}", "fn main() {\n\tdbg!(1);\n}")
+            .quote("said someone")
+            .notes("say hi")
+            .rule()
+            .finish()
+            .unwrap();
+        let md = "---\ntitle: Talk\n---\n\n# Hello\n\nSome words\n\n- one\n- two\n\n---\n\n\
+                  ## Steps\n\n1. first\n2. second\n\n```rust {1}\nfn main() {\n\tdbg!(1);\n}\n```\n\n\
+                  > said someone\n\n```notes\nsay hi\n```\n\n***\n";
+        let parsed = mkslides_from_str(md, "slides", "", &ParseOptions::default()).unwrap();
+        assert_eq!(built.title(), parsed.title());
+        assert_eq!(built.slides().len(), parsed.slides().len());
+        for (built, parsed) in built.slides().iter().zip(parsed.slides()) {
+            assert_eq!(built.notes(), parsed.notes());
+            assert_eq!(
+                render_slide_to_buffer(built, 60, 20).unwrap(),
+                render_slide_to_buffer(parsed, 60, 20).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use ratatui::prelude::Rect;
+
+use itertools::Itertools;
+
+use crate::{
+    emoji,
+    fence::Fences,
+    slide::{
+        empty_slides, expand_vars, is_url, slide_area, unclosed_fence, RenderOptions, SlideItem,
+        Slides,
+    },
+};
+
+/// terminal size slides are measured against
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 24;
+
+/// something `--check` found wrong with the deck
+pub(crate) struct Problem {
+    /// errors make the deck unfit to present, warnings are worth a look
+    pub(crate) error: bool,
+    pub(crate) msg: String,
+}
+
+impl Problem {
+    fn error(msg: String) -> Self {
+        Self { error: true, msg }
+    }
+
+    fn warning(msg: String) -> Self {
+        Self { error: false, msg }
+    }
+}
+
+/// looks for mistakes in the deck parsed from `sources`, given as pairs of
+/// a file name and its markdown split into slides on `separator`
+pub(crate) fn check(
+    slides: &mut Slides,
+    opts: &RenderOptions,
+    sources: &[(String, String)],
+    separator: &str,
+) -> Vec<Problem> {
+    let mut problems = vec![];
+    for (name, src) in sources {
+        if let Some(line) = unclosed_fence(src) {
+            problems.push(Problem::error(format!(
+                "{name}:{line}: code fence is never closed, it runs to the end of the file"
+            )));
+        }
+        for line in empty_slides(src, separator) {
+            problems.push(Problem::warning(format!(
+                "{name}:{line}: there's an empty slide next to this separator, it's left out"
+            )));
+        }
+        // code often has braces of its own, like templates, so only text is looked at
+        for (line, text) in prose_lines(src) {
+            for var in expand_vars(&text, slides.meta()).1.into_iter().unique() {
+                problems.push(Problem::warning(format!(
+                    "{name}:{line}: '{{{{{var}}}}}' isn't defined in the frontmatter and is shown as it is"
+                )));
+            }
+            for code in emoji::unknown(&text) {
+                problems.push(Problem::warning(format!(
+                    "{name}:{line}: ':{code}:' isn't one of the known emoji shortcodes and is shown as it is"
+                )));
+            }
+        }
+    }
+    for (idx, slide) in slides.slides().iter().enumerate() {
+        let label = label(idx, slide.name());
+        for item in slide.items() {
+            match item {
+                SlideItem::QR(qr) => {
+                    if let Err(e) = qr.encode() {
+                        problems.push(Problem::error(format!(
+                            "{label}: qrcode can't be encoded: {e}"
+                        )));
+                    }
+                }
+                SlideItem::Code(code) => {
+                    for range in &code.inverted {
+                        problems.push(Problem::warning(format!(
+                            "{label}: highlighted lines '{range}' end before they start and pick out nothing"
+                        )));
+                    }
+                }
+                SlideItem::Image { path, .. } if !is_url(path) && !path.exists() => {
+                    problems.push(Problem::error(format!(
+                        "{label}: image '{}' doesn't exist",
+                        path.display()
+                    )));
+                }
+                _ => {}
+            }
+        }
+    }
+    let size = Rect::new(0, 0, WIDTH, HEIGHT);
+    if opts.scroll {
+        // nothing is split into pages, slides that don't fit are scrolled
+        for (idx, slide) in slides.slides().iter().enumerate() {
+            let rows = slides.overflow(idx, slide_area(size, opts), opts);
+            if rows > 0 {
+                problems.push(Problem::warning(format!(
+                    "{} is {rows} row(s) taller than {WIDTH}x{HEIGHT} and has to be scrolled",
+                    label(idx, slide.name())
+                )));
+            }
+        }
+        return problems;
+    }
+    // measured the same way presenting does, by splitting into pages
+    slides.paginate(slide_area(size, opts), opts);
+    let mut pages = vec![];
+    for (idx, slide) in slides.slides().iter().enumerate() {
+        // slides with columns and items taller than a page aren't split, so they're cut off
+        let cut = slides.overflow(idx, slide_area(size, opts), opts);
+        match slide.part() {
+            0 => pages.push((slide.name(), 1, cut)),
+            _ => {
+                let (_, count, rows) = pages.last_mut().unwrap();
+                *count += 1;
+                *rows = cut.max(*rows);
+            }
+        }
+    }
+    for (idx, (name, count, cut)) in pages.into_iter().enumerate() {
+        let label = label(idx, name);
+        if count > 1 {
+            problems.push(Problem::warning(format!(
+                "{label} doesn't fit in {WIDTH}x{HEIGHT} and is split into {count} pages"
+            )));
+        }
+        if cut > 0 {
+            problems.push(Problem::warning(format!(
+                "{label} runs {cut} row(s) past the bottom of {WIDTH}x{HEIGHT}"
+            )));
+        }
+    }
+    problems
+}
+
+/// the lines of `src` outside code blocks with their number, inline code left out
+fn prose_lines(src: &str) -> impl Iterator<Item = (usize, String)> + '_ {
+    let mut fences = Fences::default();
+    src.lines()
+        .enumerate()
+        .filter(move |(_, line)| !fences.line(line))
+        .map(|(idx, line)| (idx + 1, line.split('`').step_by(2).collect()))
+}
+
+fn label(idx: usize, name: Option<String>) -> String {
+    match name {
+        Some(name) => format!("slide {} ('{name}')", idx + 1),
+        None => format!("slide {}", idx + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slide::{mkslides_from_str, ParseOptions};
+
+    fn problems(src: &str, opts: &RenderOptions) -> Vec<String> {
+        let mut slides = mkslides_from_str(src, "deck", "", &ParseOptions::default()).unwrap();
+        let sources = [("deck.md".into(), src.into())];
+        check(&mut slides, opts, &sources, "---")
+            .into_iter()
+            .map(|problem| problem.msg)
+            .collect()
+    }
+
+    #[test]
+    fn finds_empty_slides() {
+        let src = "---\ntitle: T\n---\n\n# A\n\n---\n\n---\n\n# B\n\n---\n";
+        assert_eq!(
+            problems(src, &RenderOptions::default()),
+            [
+                "deck.md:9: there's an empty slide next to this separator, it's left out",
+                "deck.md:13: there's an empty slide next to this separator, it's left out",
+            ]
+        );
+    }
+
+    #[test]
+    fn variables_in_code_are_left_alone() {
+        let src = "# {{a}}\n\n```\n{{b}}\n```\n\n`{{c}}`\n";
+        assert_eq!(
+            problems(src, &RenderOptions::default()),
+            ["deck.md:1: '{{a}}' isn't defined in the frontmatter and is shown as it is"]
+        );
+    }
+
+    #[test]
+    fn scrolled_slides_arent_split() {
+        let items = (0..40).map(|i| format!("- {i}\n")).collect::<String>();
+        let src = format!("# Long\n\n{items}");
+        let opts = RenderOptions {
+            scroll: true,
+            ..RenderOptions::default()
+        };
+        let problems = problems(&src, &opts);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].ends_with("has to be scrolled"), "{problems:?}");
+    }
+}
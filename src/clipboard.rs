@@ -0,0 +1,11 @@
+use std::io::Write;
+
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// asks the terminal to put `text` on the system clipboard with an OSC 52
+/// escape, it works over ssh too and terminals that don't know it ignore it
+pub(crate) fn copy(out: &mut impl Write, text: &str) -> Result<()> {
+    write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    Ok(out.flush()?)
+}
@@ -0,0 +1,196 @@
+/// shortcodes and the emoji they stand for, sorted by name so they can be
+/// binary searched
+///
+/// only emoji drawn two columns wide on their own are listed, the ones that
+/// need a variation selector to look like emoji would throw the layout off
+const SHORTCODES: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("alarm_clock", "⏰"),
+    ("apple", "🍎"),
+    ("balloon", "🎈"),
+    ("bang", "💥"),
+    ("beer", "🍺"),
+    ("bell", "🔔"),
+    ("books", "📚"),
+    ("boom", "💥"),
+    ("brain", "🧠"),
+    ("bug", "🐛"),
+    ("bulb", "💡"),
+    ("calendar", "📅"),
+    ("cat", "🐱"),
+    ("chart_with_downwards_trend", "📉"),
+    ("chart_with_upwards_trend", "📈"),
+    ("clap", "👏"),
+    ("clipboard", "📋"),
+    ("coffee", "☕"),
+    ("computer", "💻"),
+    ("construction", "🚧"),
+    ("cool", "🆒"),
+    ("crab", "🦀"),
+    ("crossed_fingers", "🤞"),
+    ("crown", "👑"),
+    ("dart", "🎯"),
+    ("dog", "🐶"),
+    ("earth_africa", "🌍"),
+    ("earth_americas", "🌎"),
+    ("earth_asia", "🌏"),
+    ("exclamation", "❗"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("flushed", "😳"),
+    ("gift", "🎁"),
+    ("globe_with_meridians", "🌐"),
+    ("grin", "😁"),
+    ("grinning", "😀"),
+    ("hammer", "🔨"),
+    ("heart_eyes", "😍"),
+    ("hourglass", "⌛"),
+    ("hugs", "🤗"),
+    ("joy", "😂"),
+    ("key", "🔑"),
+    ("laughing", "😆"),
+    ("lock", "🔒"),
+    ("mag", "🔍"),
+    ("memo", "📝"),
+    ("microphone", "🎤"),
+    ("money_with_wings", "💸"),
+    ("moneybag", "💰"),
+    ("muscle", "💪"),
+    ("new", "🆕"),
+    ("ok", "🆗"),
+    ("ok_hand", "👌"),
+    ("package", "📦"),
+    ("pencil", "📝"),
+    ("point_down", "👇"),
+    ("point_left", "👈"),
+    ("point_right", "👉"),
+    ("point_up_2", "👆"),
+    ("pray", "🙏"),
+    ("pushpin", "📌"),
+    ("question", "❓"),
+    ("rainbow", "🌈"),
+    ("raised_hands", "🙌"),
+    ("rocket", "🚀"),
+    ("rofl", "🤣"),
+    ("rotating_light", "🚨"),
+    ("scream", "😱"),
+    ("see_no_evil", "🙈"),
+    ("shrug", "🤷"),
+    ("slightly_smiling_face", "🙂"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("snake", "🐍"),
+    ("sob", "😭"),
+    ("sparkles", "✨"),
+    ("star", "⭐"),
+    ("star2", "🌟"),
+    ("sunglasses", "😎"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("trophy", "🏆"),
+    ("tv", "📺"),
+    ("unicorn", "🦄"),
+    ("wave", "👋"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("zap", "⚡"),
+    ("zzz", "💤"),
+];
+
+/// replaces every `:name:` in `text` with its emoji, unknown names are left as they are
+pub(crate) fn expand(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let emoji = after
+            .find(':')
+            .map(|end| &after[..end])
+            .and_then(|name| Some((name, lookup(name)?)));
+        match emoji {
+            Some((name, emoji)) => {
+                out.push_str(emoji);
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                out.push(':');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// the names written like shortcodes in `text` that aren't in the table, they're
+/// shown as typed
+pub(crate) fn unknown(text: &str) -> Vec<&str> {
+    let mut names = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        let after = &rest[start + 1..];
+        let name = after.find(':').map(|end| &after[..end]).filter(|name| {
+            // `10:30:00` or `a: b` aren't meant as shortcodes
+            name.chars().any(|c| c.is_ascii_lowercase())
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_+-".contains(c))
+        });
+        match name {
+            Some(name) => {
+                if lookup(name).is_none() {
+                    names.push(name);
+                }
+                rest = &after[name.len() + 1..];
+            }
+            None => rest = after,
+        }
+    }
+    names
+}
+
+fn lookup(name: &str) -> Option<&'static str> {
+    let idx = SHORTCODES
+        .binary_search_by_key(&name, |(name, _)| name)
+        .ok()?;
+    Some(SHORTCODES[idx].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_shortcodes() {
+        assert_eq!(expand("ship it :rocket: :tada:"), "ship it 🚀 🎉");
+        assert_eq!(expand(":smile::wave:"), "😄👋");
+    }
+
+    #[test]
+    fn keeps_unknown_names_and_colons() {
+        assert_eq!(expand(":not_an_emoji:"), ":not_an_emoji:");
+        assert_eq!(expand("at 10:30:00, note: :x:"), "at 10:30:00, note: ❌");
+    }
+
+    #[test]
+    fn finds_unknown_shortcodes() {
+        assert_eq!(
+            unknown(":rocket: :heart: at 10:30:00, note: :warning:"),
+            ["heart", "warning"]
+        );
+        assert!(unknown("std::fs and a: b: c").is_empty());
+    }
+
+    #[test]
+    fn shortcodes_are_sorted() {
+        // lookup binary searches the table
+        assert!(SHORTCODES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+}
@@ -0,0 +1,353 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use ratatui::style::{Color, Modifier};
+use serde::Serialize;
+
+use crate::{
+    math,
+    slide::{columns, Bullet, Run, SlideItem, Slides},
+    theme::color_name,
+};
+
+/// what `--json` prints about the deck
+#[derive(Debug, Serialize)]
+pub(crate) struct DeckSummary<'a> {
+    pub(crate) title: &'a str,
+    pub(crate) count: usize,
+    pub(crate) slides: Vec<SlideSummary<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct SlideSummary<'a> {
+    /// counting from 1 like the slide counter
+    pub(crate) index: usize,
+    pub(crate) id: Option<&'a str>,
+    pub(crate) heading: Option<String>,
+    /// the kind of each item in order, like `heading` or `code`
+    pub(crate) items: Vec<&'static str>,
+}
+
+/// describes the slides and their items as pretty printed json
+pub(crate) fn to_json(slides: &Slides) -> Result<String> {
+    let summary = DeckSummary {
+        title: slides.title(),
+        count: slides.slides().len(),
+        slides: slides
+            .slides()
+            .iter()
+            .enumerate()
+            .map(|(idx, slide)| SlideSummary {
+                index: idx + 1,
+                id: slide.id(),
+                heading: slide.heading(),
+                items: slide.items().iter().map(SlideItem::kind).collect(),
+            })
+            .collect(),
+    };
+    Ok(serde_json::to_string_pretty(&summary)?)
+}
+
+/// renders the whole deck as a standalone html page, one `<section>` per slide
+pub(crate) fn to_html(slides: &Slides) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n",
+        escape(slides.title())
+    );
+    for (idx, slide) in slides.slides().iter().enumerate() {
+        let mut style = vec![];
+        if let Some(align) = slide.align() {
+            style.push(format!(
+                "text-align:{}",
+                format!("{align:?}").to_lowercase()
+            ));
+        }
+        if let Some(bg) = slide.bg().and_then(css_color) {
+            style.push(format!("background:{bg}"));
+        }
+        if style.is_empty() {
+            out.push_str("<section class=\"slide\">\n");
+        } else {
+            let _ = writeln!(
+                out,
+                "<section class=\"slide\" style=\"{}\">",
+                style.join(";")
+            );
+        }
+        let _ = writeln!(
+            out,
+            "<div class=\"progress\" style=\"width:{:.1}%\"></div>",
+            slides.progress(idx) * 100.0
+        );
+        match slide.column_break() {
+            Some(split) => {
+                let [head, left, right] = columns(slide.items(), split);
+                head.iter().for_each(|item| item_to_html(item, &mut out));
+                out.push_str("<div class=\"columns\">\n<div>\n");
+                left.iter().for_each(|item| item_to_html(item, &mut out));
+                out.push_str("</div>\n<div>\n");
+                right.iter().for_each(|item| item_to_html(item, &mut out));
+                out.push_str("</div>\n</div>\n");
+            }
+            None => slide
+                .items()
+                .iter()
+                .for_each(|item| item_to_html(item, &mut out)),
+        }
+        let _ = writeln!(out, "<div class=\"counter\">{}</div>", slides.counter(idx));
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// renders the whole deck as markdown, one slide per `---` separated block
+pub(crate) fn to_markdown(slides: &Slides) -> String {
+    let meta = slides.meta();
+    let mut out = String::new();
+    if !meta.is_empty() {
+        // serde_yaml already starts its output with the opening fence
+        let yaml = serde_yaml::to_string(meta).unwrap_or_default();
+        let _ = write!(out, "{}\n---\n\n", yaml.trim_end());
+    }
+    // the contents slide is made again when the export is presented with `--toc`
+    out += &slides
+        .slides()
+        .iter()
+        .filter(|slide| !slide.is_toc())
+        .map(|slide| slide.to_markdown())
+        .collect::<Vec<_>>()
+        .join("\n\n---\n\n");
+    out.push('\n');
+    out
+}
+
+const STYLE: &str = "body{margin:0;font-family:sans-serif}\
+.slide{position:relative;box-sizing:border-box;min-height:100vh;padding:4rem;page-break-after:always;border-bottom:1px solid #ccc}\
+.progress{position:absolute;top:0;left:0;height:.3rem;background:#00f}\
+.counter{position:absolute;right:1rem;bottom:.5rem;opacity:.7}\
+pre{border-left:2px solid #888;padding-left:1rem}\
+blockquote{border-left:2px solid #888;margin-left:1rem;padding-left:1rem;font-style:italic;opacity:.8}\
+.columns{display:flex;gap:2rem}.columns>div{flex:1;min-width:0}\
+.footnotes{font-size:.8em;opacity:.7;border-top:1px solid #888;padding-top:.5rem}\
+.notes{display:none}";
+
+fn item_to_html(item: &SlideItem, out: &mut String) {
+    match item {
+        SlideItem::Heading(level, runs) => {
+            let _ = writeln!(out, "<h{level}>{}</h{level}>", runs_to_html(runs));
+        }
+        SlideItem::Paragraph(runs) => {
+            let _ = writeln!(out, "<p>{}</p>", runs_to_html(runs));
+        }
+        SlideItem::Bullets(bullets) => bullets_to_html(bullets, out),
+        SlideItem::Quote(src) => {
+            let _ = writeln!(out, "<blockquote>{}</blockquote>", escape(src));
+        }
+        SlideItem::DefList(entries) => {
+            out.push_str("<dl>\n");
+            for (term, details) in entries {
+                let _ = writeln!(out, "<dt>{}</dt><dd>{}</dd>", escape(term), escape(details));
+            }
+            out.push_str("</dl>\n");
+        }
+        SlideItem::Footnotes(notes) => {
+            out.push_str("<ol class=\"footnotes\">\n");
+            for note in notes {
+                let _ = writeln!(out, "<li>{}</li>", escape(note));
+            }
+            out.push_str("</ol>\n");
+        }
+        SlideItem::Code(code) => {
+            let class = if code.lang.is_empty() {
+                String::new()
+            } else {
+                format!(" class=\"language-{}\"", escape(&code.lang))
+            };
+            let src = code
+                .shown()
+                .lines()
+                .enumerate()
+                .map(|(idx, line)| {
+                    if code.highlight.contains(&idx) {
+                        format!("<mark>{}</mark>", escape(line))
+                    } else {
+                        escape(line)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let _ = writeln!(out, "<pre><code{class}>{src}</code></pre>");
+        }
+        SlideItem::QR(qr) => match qr.encode() {
+            Ok(code) => {
+                let svg = code
+                    .render::<qrcode::render::svg::Color>()
+                    .min_dimensions(200, 200)
+                    .build();
+                // drop the xml prolog, it's not allowed inline in html
+                let svg = svg.find("<svg").map_or(svg.as_str(), |i| &svg[i..]);
+                let caption = qr.caption.as_deref().map_or(String::new(), |caption| {
+                    format!("<figcaption>{}</figcaption>", escape(caption))
+                });
+                let _ = writeln!(out, "<figure class=\"qrcode\">{svg}{caption}</figure>");
+            }
+            Err(e) => {
+                let _ = writeln!(out, "<p class=\"error\">QR encoding failed: {e}</p>");
+            }
+        },
+        SlideItem::Image { path, alt } => {
+            let _ = writeln!(
+                out,
+                "<img src=\"{}\" alt=\"{}\">",
+                escape(&path.to_string_lossy()),
+                escape(alt)
+            );
+        }
+        SlideItem::Notes(src) => {
+            let _ = writeln!(out, "<aside class=\"notes\">{}</aside>", escape(src));
+        }
+        SlideItem::Rule => out.push_str("<hr>\n"),
+        SlideItem::Math(src) => match math::unicode(src) {
+            Some(text) => {
+                let _ = writeln!(out, "<p class=\"math\">{}</p>", escape(&text));
+            }
+            None => {
+                let _ = writeln!(out, "<pre class=\"math\">{}</pre>", escape(src));
+            }
+        },
+        SlideItem::Table { headers, rows } => {
+            out.push_str("<table>\n<tr>");
+            for cell in headers {
+                let _ = write!(out, "<th>{}</th>", escape(cell));
+            }
+            out.push_str("</tr>\n");
+            for row in rows {
+                out.push_str("<tr>");
+                for cell in row
+                    .iter()
+                    .map(String::as_str)
+                    .chain(std::iter::repeat(""))
+                    .take(headers.len())
+                {
+                    let _ = write!(out, "<td>{}</td>", escape(cell));
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("</table>\n");
+        }
+    }
+}
+
+/// rebuilds the nested `<ul>`/`<ol>` structure from the flat depth list
+fn bullets_to_html(bullets: &[Bullet], out: &mut String) {
+    // closing tag of every open list
+    let mut open: Vec<&str> = vec![];
+    for bullet in bullets {
+        if open.len() > bullet.depth {
+            while open.len() > bullet.depth + 1 {
+                let _ = write!(out, "</li></{}>", open.pop().unwrap_or("ul"));
+            }
+            out.push_str("</li>\n");
+        }
+        while open.len() <= bullet.depth {
+            match bullet.number {
+                Some(n) => {
+                    let _ = write!(out, "<ol start=\"{n}\">");
+                    open.push("ol");
+                }
+                None => {
+                    out.push_str("<ul>");
+                    open.push("ul");
+                }
+            }
+        }
+        let checkbox = match bullet.checked {
+            Some(true) => "<input type=\"checkbox\" checked disabled> ",
+            Some(false) => "<input type=\"checkbox\" disabled> ",
+            None => "",
+        };
+        let _ = write!(out, "<li>{checkbox}{}", runs_to_html(&bullet.text));
+    }
+    while let Some(tag) = open.pop() {
+        let _ = write!(out, "</li></{tag}>");
+    }
+    out.push('\n');
+}
+
+fn runs_to_html(runs: &[Run]) -> String {
+    runs.iter()
+        .map(|run| {
+            let mut html = escape(&run.text).replace('\n', "<br>\n");
+            if run.code {
+                html = format!("<code>{html}</code>");
+            }
+            if run.modifier.contains(Modifier::CROSSED_OUT) {
+                html = format!("<del>{html}</del>");
+            }
+            if run.modifier.contains(Modifier::ITALIC) {
+                html = format!("<em>{html}</em>");
+            }
+            if run.modifier.contains(Modifier::BOLD) {
+                html = format!("<strong>{html}</strong>");
+            }
+            match &run.link {
+                Some(url) => format!("<a href=\"{}\">{html}</a>", escape(url)),
+                None => html,
+            }
+        })
+        .collect()
+}
+
+fn escape(src: &str) -> String {
+    src.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// the css spelling of a terminal color, `None` for ones that depend on the terminal
+fn css_color(color: Color) -> Option<String> {
+    let name = match color {
+        Color::Rgb(..) => return Some(color_name(color)),
+        Color::Reset | Color::Indexed(_) => return None,
+        Color::DarkGray => "dimgray",
+        Color::Gray => "silver",
+        Color::LightRed => "lightcoral",
+        Color::LightMagenta => "violet",
+        Color::LightBlue => "lightskyblue",
+        color => return Some(format!("{color:?}").to_lowercase()),
+    };
+    Some(name.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slide::{mkslides_from_str, ParseOptions};
+
+    fn export(src: &str) -> String {
+        let slides = mkslides_from_str(src, "deck", ".", &ParseOptions::default()).unwrap();
+        to_markdown(&slides)
+    }
+
+    #[test]
+    fn exports_are_stable() {
+        let deck = "---\ntitle: Talk\nmath: true\nevent: RustConf\n---\n\n\
+                    # {{event}}\n\n- one\n  - two\n\n> quoted\n\n---\n\n\
+                    ## Code\n\n```rust {1}\nfn main() {}\n```\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n\
+                    ```notes\nsay hi\n```\n";
+        for src in [deck, include_str!("../example.md")] {
+            let once = export(src);
+            assert_eq!(export(&once), once);
+        }
+        let once = export(deck);
+        assert!(
+            once.starts_with("---\ntitle: Talk\nmath: true\nevent: RustConf\n---\n"),
+            "{once}"
+        );
+        assert!(once.ends_with("```notes\nsay hi\n```\n"), "{once}");
+    }
+}
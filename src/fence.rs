@@ -0,0 +1,85 @@
+/// follows fenced code blocks line by line like commonmark does, so separators
+/// and directives written inside code are left alone
+#[derive(Debug, Default)]
+pub(crate) struct Fences {
+    /// character and length of the fence that opened the current block
+    open: Option<(char, usize)>,
+}
+
+impl Fences {
+    /// takes the next line, `true` if it opens, closes or is inside a code block
+    pub(crate) fn line(&mut self, line: &str) -> bool {
+        let trimmed = line.trim();
+        let run = |c: char| trimmed.chars().take_while(|&x| x == c).count();
+        match self.open {
+            Some((c, len)) => {
+                // only a fence of the same character, at least as long and
+                // without an info string closes the block
+                if run(c) >= len && trimmed.trim_start_matches(c).is_empty() {
+                    self.open = None;
+                }
+                true
+            }
+            None => {
+                let Some(c) = trimmed.chars().next().filter(|c| matches!(c, '`' | '~')) else {
+                    return false;
+                };
+                let len = run(c);
+                // backticks in the info string make it inline code instead
+                if len < 3 || (c == '`' && trimmed[len..].contains('`')) {
+                    return false;
+                }
+                self.open = Some((c, len));
+                true
+            }
+        }
+    }
+
+    /// whether the lines so far leave a code block open
+    pub(crate) fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// which of `lines` are code, fences included
+    fn code(lines: &[&str]) -> Vec<bool> {
+        let mut fences = Fences::default();
+        lines.iter().map(|line| fences.line(line)).collect()
+    }
+
+    #[test]
+    fn closes_on_the_same_fence() {
+        assert_eq!(
+            code(&["text", "```rust", "---", "```", "after"]),
+            [false, true, true, true, false]
+        );
+        assert_eq!(code(&["~~~", "```", "~~~", "x"]), [true, true, true, false]);
+    }
+
+    #[test]
+    fn shorter_fences_dont_close() {
+        let lines = ["````md", "```rust", "---", "```", "````", "x"];
+        assert_eq!(code(&lines), [true, true, true, true, true, false]);
+        assert_eq!(code(&["```", "````", "x"]), [true, true, false]);
+    }
+
+    #[test]
+    fn info_strings_dont_close() {
+        assert_eq!(
+            code(&["```", "```rust", "---", "```", "x"]),
+            [true, true, true, true, false]
+        );
+    }
+
+    #[test]
+    fn inline_code_isnt_a_fence() {
+        assert_eq!(code(&["``", "```a`b```", "x"]), [false, false, false]);
+        let mut fences = Fences::default();
+        fences.line("```");
+        assert!(fences.is_open());
+    }
+}
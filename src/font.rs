@@ -0,0 +1,608 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+/// monospace fonts looked for when no font is given, the first one found is used
+const FONT_PATHS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/TTF/DejaVuSansMono.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/dejavu-sans-mono-fonts/DejaVuSansMono.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationMono-Regular.ttf",
+    "/usr/share/fonts/liberation-mono/LiberationMono-Regular.ttf",
+    "/usr/share/fonts/truetype/noto/NotoSansMono-Regular.ttf",
+    "/System/Library/Fonts/Menlo.ttc",
+    "/Library/Fonts/Courier New.ttf",
+    "C:\\Windows\\Fonts\\consola.ttf",
+];
+
+/// a truetype font, just enough of one to draw the glyphs of a terminal grid
+pub(crate) struct Font {
+    data: Vec<u8>,
+    units_per_em: f32,
+    ascender: f32,
+    descender: f32,
+    advance: f32,
+    num_glyphs: u16,
+    long_loca: bool,
+    loca: usize,
+    glyf: usize,
+    cmap: Cmap,
+}
+
+/// coverage of a rendered glyph, from 0 to 1 per pixel
+pub(crate) struct Glyph {
+    /// offset of the bitmap from the left edge and the top of the line
+    pub(crate) left: i32,
+    pub(crate) top: i32,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) coverage: Vec<f32>,
+}
+
+enum Cmap {
+    Segments(usize),
+    Groups(usize),
+}
+
+/// a point of a glyph outline in font units, `true` if it lies on the curve
+type Point = (f32, f32, bool);
+
+/// the first of `paths` that is a file
+fn find(paths: &[&str]) -> Result<PathBuf> {
+    if let Some(path) = paths.iter().map(PathBuf::from).find(|path| path.is_file()) {
+        return Ok(path);
+    }
+    let mut msg = "no monospace font found, looked for one at".to_owned();
+    for path in paths {
+        msg.push_str("\n    ");
+        msg.push_str(path);
+    }
+    msg.push_str("\ninstall DejaVu Sans Mono or pass a .ttf with --png-font");
+    bail!(msg)
+}
+
+impl Font {
+    /// reads the font at `path`, or the first monospace font installed in a usual place
+    pub(crate) fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(path) => path.to_owned(),
+            None => find(FONT_PATHS)?,
+        };
+        let data = std::fs::read(&path)
+            .with_context(|| format!("could not read font '{}'", path.display()))?;
+        Self::parse(data).with_context(|| format!("invalid font '{}'", path.display()))
+    }
+
+    fn parse(data: Vec<u8>) -> Result<Self> {
+        let mut font = Font {
+            data,
+            units_per_em: 0.0,
+            ascender: 0.0,
+            descender: 0.0,
+            advance: 0.0,
+            num_glyphs: 0,
+            long_loca: false,
+            loca: 0,
+            glyf: 0,
+            cmap: Cmap::Segments(0),
+        };
+        // a collection starts with a list of fonts, use the first one
+        let start = if font.data.starts_with(b"ttcf") {
+            font.u32(12)? as usize
+        } else {
+            0
+        };
+        let table = |tag: &[u8]| -> Result<usize> {
+            let count = font.u16(start + 4)? as usize;
+            (0..count)
+                .map(|i| start + 12 + i * 16)
+                .find(|&record| font.data.get(record..record + 4) == Some(tag))
+                .map(|record| font.u32(record + 8).map(|offset| offset as usize))
+                .with_context(|| {
+                    format!(
+                        "no '{}' table, only truetype outlines are supported",
+                        String::from_utf8_lossy(tag)
+                    )
+                })?
+        };
+        let (head, hhea, hmtx, maxp, cmap) = (
+            table(b"head")?,
+            table(b"hhea")?,
+            table(b"hmtx")?,
+            table(b"maxp")?,
+            table(b"cmap")?,
+        );
+        let (loca, glyf) = (table(b"loca")?, table(b"glyf")?);
+        let subtable = font.cmap_subtable(cmap)?;
+        font.units_per_em = font.u16(head + 18)? as f32;
+        font.long_loca = font.i16(head + 50)? != 0;
+        font.ascender = font.i16(hhea + 4)? as f32;
+        font.descender = font.i16(hhea + 6)? as f32;
+        font.advance = font.u16(hmtx)? as f32;
+        font.num_glyphs = font.u16(maxp + 4)?;
+        font.loca = loca;
+        font.glyf = glyf;
+        font.cmap = subtable;
+        if font.units_per_em == 0.0 || font.ascender <= font.descender {
+            bail!("broken font metrics");
+        }
+        Ok(font)
+    }
+
+    /// the unicode subtable of the `cmap` table at `cmap`
+    fn cmap_subtable(&self, cmap: usize) -> Result<Cmap> {
+        let count = self.u16(cmap + 2)? as usize;
+        let mut found = None;
+        for i in 0..count {
+            let record = cmap + 4 + i * 8;
+            let (platform, encoding) = (self.u16(record)?, self.u16(record + 2)?);
+            let offset = cmap + self.u32(record + 4)? as usize;
+            let unicode = platform == 0 || (platform == 3 && matches!(encoding, 1 | 10));
+            if !unicode {
+                continue;
+            }
+            match self.u16(offset)? {
+                // full unicode beats the basic plane
+                12 => return Ok(Cmap::Groups(offset)),
+                4 => found = Some(Cmap::Segments(offset)),
+                _ => {}
+            }
+        }
+        found.context("no unicode character map")
+    }
+
+    /// pixel size of one cell when a line is `height` pixels tall
+    pub(crate) fn cell_width(&self, height: usize) -> usize {
+        (self.advance * self.scale(height)).round().max(1.0) as usize
+    }
+
+    /// pixels per font unit for lines `height` pixels tall
+    fn scale(&self, height: usize) -> f32 {
+        height as f32 / (self.ascender - self.descender)
+    }
+
+    /// draws `c` for a line `height` pixels tall, `None` if the font doesn't have it
+    pub(crate) fn glyph(&self, c: char, height: usize) -> Option<Glyph> {
+        let id = self.glyph_id(c)?;
+        let mut contours = vec![];
+        self.outline(id, [1.0, 0.0, 0.0, 1.0, 0.0, 0.0], &mut contours, 0);
+        let scale = self.scale(height);
+        let baseline = self.ascender * scale;
+        // font units point up from the baseline, pixels point down from the top
+        let contours = contours
+            .iter()
+            .map(|contour| {
+                contour
+                    .iter()
+                    .map(|&(x, y, on)| (x * scale, baseline - y * scale, on))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let points = contours.iter().flatten();
+        let (min_x, min_y) = points
+            .clone()
+            .fold((f32::MAX, f32::MAX), |(x, y), p| (x.min(p.0), y.min(p.1)));
+        let (max_x, max_y) =
+            points.fold((f32::MIN, f32::MIN), |(x, y), p| (x.max(p.0), y.max(p.1)));
+        if min_x > max_x {
+            // a space, nothing to draw but the font has it
+            return Some(Glyph {
+                left: 0,
+                top: 0,
+                width: 0,
+                height: 0,
+                coverage: vec![],
+            });
+        }
+        let (left, top) = (min_x.floor(), min_y.floor());
+        let mut raster = Raster::new(
+            (max_x.ceil() - left) as usize + 2,
+            (max_y.ceil() - top) as usize + 1,
+        );
+        for contour in &contours {
+            let contour = contour
+                .iter()
+                .map(|&(x, y, on)| (x - left, y - top, on))
+                .collect::<Vec<_>>();
+            for_each_line(&contour, |a, b| raster.line(a, b));
+        }
+        Some(Glyph {
+            left: left as i32,
+            top: top as i32,
+            width: raster.width,
+            height: raster.height,
+            coverage: raster.coverage(),
+        })
+    }
+
+    fn glyph_id(&self, c: char) -> Option<u16> {
+        let c = c as u32;
+        let id = match self.cmap {
+            Cmap::Groups(table) => {
+                let count = self.u32(table + 12).ok()? as usize;
+                (0..count).find_map(|i| {
+                    let group = table + 16 + i * 12;
+                    let (first, last) = (self.u32(group).ok()?, self.u32(group + 4).ok()?);
+                    let start = self.u32(group + 8).ok()?;
+                    (first..=last).contains(&c).then(|| start + c - first)
+                })? as u16
+            }
+            Cmap::Segments(table) => {
+                let c = u16::try_from(c).ok()?;
+                let segments = self.u16(table + 6).ok()? as usize / 2;
+                let ends = table + 14;
+                let starts = ends + segments * 2 + 2;
+                let deltas = starts + segments * 2;
+                let ranges = deltas + segments * 2;
+                let i =
+                    (0..segments).find(|&i| self.u16(ends + i * 2).is_ok_and(|end| end >= c))?;
+                let start = self.u16(starts + i * 2).ok()?;
+                if start > c {
+                    return None;
+                }
+                let delta = self.u16(deltas + i * 2).ok()?;
+                let range = self.u16(ranges + i * 2).ok()? as usize;
+                if range == 0 {
+                    c.wrapping_add(delta)
+                } else {
+                    let at = ranges + i * 2 + range + (c - start) as usize * 2;
+                    match self.u16(at).ok()? {
+                        0 => 0,
+                        id => id.wrapping_add(delta),
+                    }
+                }
+            }
+        };
+        (id != 0 && id < self.num_glyphs).then_some(id)
+    }
+
+    /// byte range of glyph `id` in the `glyf` table
+    fn glyph_range(&self, id: u16) -> Option<(usize, usize)> {
+        let id = id as usize;
+        let (start, end) = if self.long_loca {
+            (
+                self.u32(self.loca + id * 4).ok()? as usize,
+                self.u32(self.loca + id * 4 + 4).ok()? as usize,
+            )
+        } else {
+            (
+                self.u16(self.loca + id * 2).ok()? as usize * 2,
+                self.u16(self.loca + id * 2 + 2).ok()? as usize * 2,
+            )
+        };
+        (end > start).then_some((self.glyf + start, self.glyf + end))
+    }
+
+    /// appends the contours of glyph `id` moved by the affine `transform`
+    fn outline(&self, id: u16, transform: [f32; 6], out: &mut Vec<Vec<Point>>, depth: usize) {
+        let Some((start, _)) = self.glyph_range(id) else {
+            return;
+        };
+        let Ok(contours) = self.i16(start) else {
+            return;
+        };
+        // composite glyphs can refer to each other, don't follow a loop forever
+        if contours < 0 && depth < 8 {
+            self.composite(start + 10, transform, out, depth);
+        } else if let Some(contours) = self.simple(start, contours as usize) {
+            let [a, b, c, d, e, f] = transform;
+            out.extend(contours.into_iter().map(|contour| {
+                contour
+                    .into_iter()
+                    .map(|(x, y, on)| (a * x + c * y + e, b * x + d * y + f, on))
+                    .collect()
+            }));
+        }
+    }
+
+    fn simple(&self, start: usize, contours: usize) -> Option<Vec<Vec<Point>>> {
+        let ends = (0..contours)
+            .map(|i| self.u16(start + 10 + i * 2).map(|end| end as usize))
+            .collect::<Result<Vec<_>>>()
+            .ok()?;
+        let count = ends.last().map_or(0, |end| end + 1);
+        let instructions = self.u16(start + 10 + contours * 2).ok()? as usize;
+        let mut at = start + 12 + contours * 2 + instructions;
+        let mut flags = Vec::with_capacity(count);
+        while flags.len() < count {
+            let flag = *self.data.get(at)?;
+            at += 1;
+            flags.push(flag);
+            if flag & 0x08 != 0 {
+                let repeat = *self.data.get(at)?;
+                at += 1;
+                flags.extend(std::iter::repeat_n(flag, repeat as usize));
+            }
+        }
+        flags.truncate(count);
+        let mut coords = |short: u8, same: u8| -> Option<Vec<f32>> {
+            let mut value = 0i32;
+            let mut out = Vec::with_capacity(count);
+            for &flag in &flags {
+                if flag & short != 0 {
+                    let delta = *self.data.get(at)? as i32;
+                    at += 1;
+                    value += if flag & same != 0 { delta } else { -delta };
+                } else if flag & same == 0 {
+                    value += self.i16(at).ok()? as i32;
+                    at += 2;
+                }
+                out.push(value as f32);
+            }
+            Some(out)
+        };
+        let xs = coords(0x02, 0x10)?;
+        let ys = coords(0x04, 0x20)?;
+        let mut out = vec![];
+        let mut first = 0;
+        for end in ends {
+            out.push(
+                (first..=end.min(count - 1))
+                    .map(|i| (xs[i], ys[i], flags[i] & 0x01 != 0))
+                    .collect(),
+            );
+            first = end + 1;
+        }
+        Some(out)
+    }
+
+    fn composite(
+        &self,
+        mut at: usize,
+        transform: [f32; 6],
+        out: &mut Vec<Vec<Point>>,
+        depth: usize,
+    ) {
+        loop {
+            let (Ok(flags), Ok(id)) = (self.u16(at), self.u16(at + 2)) else {
+                return;
+            };
+            at += 4;
+            let (dx, dy) = if flags & 0x0001 != 0 {
+                at += 4;
+                (self.i16(at - 4), self.i16(at - 2))
+            } else {
+                at += 2;
+                (
+                    self.data.get(at - 2).map(|&b| b as i8 as i16).context(""),
+                    self.data.get(at - 1).map(|&b| b as i8 as i16).context(""),
+                )
+            };
+            let (Ok(dx), Ok(dy)) = (dx, dy) else {
+                return;
+            };
+            let f2dot14 = |at: usize| self.i16(at).map(|v| v as f32 / 16384.0).unwrap_or(1.0);
+            let (a, b, c, d) = if flags & 0x0008 != 0 {
+                at += 2;
+                let scale = f2dot14(at - 2);
+                (scale, 0.0, 0.0, scale)
+            } else if flags & 0x0040 != 0 {
+                at += 4;
+                (f2dot14(at - 4), 0.0, 0.0, f2dot14(at - 2))
+            } else if flags & 0x0080 != 0 {
+                at += 8;
+                (
+                    f2dot14(at - 8),
+                    f2dot14(at - 6),
+                    f2dot14(at - 4),
+                    f2dot14(at - 2),
+                )
+            } else {
+                (1.0, 0.0, 0.0, 1.0)
+            };
+            // only offsets are supported, matching points is rare in practice
+            let (dx, dy) = if flags & 0x0002 != 0 {
+                (dx as f32, dy as f32)
+            } else {
+                (0.0, 0.0)
+            };
+            let [ta, tb, tc, td, te, tf] = transform;
+            let combined = [
+                ta * a + tc * b,
+                tb * a + td * b,
+                ta * c + tc * d,
+                tb * c + td * d,
+                ta * dx + tc * dy + te,
+                tb * dx + td * dy + tf,
+            ];
+            self.outline(id, combined, out, depth + 1);
+            if flags & 0x0020 == 0 {
+                return;
+            }
+        }
+    }
+
+    fn u16(&self, at: usize) -> Result<u16> {
+        let bytes = self.data.get(at..at + 2).context("font data ends early")?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i16(&self, at: usize) -> Result<i16> {
+        self.u16(at).map(|v| v as i16)
+    }
+
+    fn u32(&self, at: usize) -> Result<u32> {
+        let bytes = self.data.get(at..at + 4).context("font data ends early")?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+/// calls `line` for every straight piece of a closed contour, curves are split up
+fn for_each_line(contour: &[Point], mut line: impl FnMut((f32, f32), (f32, f32))) {
+    let Some(start) = contour.iter().position(|p| p.2) else {
+        return;
+    };
+    let n = contour.len();
+    let mut from = (contour[start].0, contour[start].1);
+    let mut control: Option<(f32, f32)> = None;
+    for i in 1..=n {
+        let (x, y, on) = contour[(start + i) % n];
+        match (on, control) {
+            (true, None) => {
+                line(from, (x, y));
+                from = (x, y);
+            }
+            (true, Some(c)) => {
+                curve(from, c, (x, y), &mut line);
+                from = (x, y);
+                control = None;
+            }
+            (false, None) => control = Some((x, y)),
+            (false, Some(c)) => {
+                // two control points in a row have an implied point halfway between
+                let mid = ((c.0 + x) / 2.0, (c.1 + y) / 2.0);
+                curve(from, c, mid, &mut line);
+                from = mid;
+                control = Some((x, y));
+            }
+        }
+    }
+}
+
+fn curve(
+    from: (f32, f32),
+    control: (f32, f32),
+    to: (f32, f32),
+    line: &mut impl FnMut((f32, f32), (f32, f32)),
+) {
+    const STEPS: usize = 8;
+    let mut prev = from;
+    for step in 1..=STEPS {
+        let t = step as f32 / STEPS as f32;
+        let u = 1.0 - t;
+        let point = (
+            u * u * from.0 + 2.0 * u * t * control.0 + t * t * to.0,
+            u * u * from.1 + 2.0 * u * t * control.1 + t * t * to.1,
+        );
+        line(prev, point);
+        prev = point;
+    }
+}
+
+/// accumulates the signed area every line covers, summing a row gives the coverage
+struct Raster {
+    width: usize,
+    height: usize,
+    area: Vec<f32>,
+}
+
+impl Raster {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            area: vec![0.0; width * height + 1],
+        }
+    }
+
+    fn line(&mut self, from: (f32, f32), to: (f32, f32)) {
+        if (from.1 - to.1).abs() < f32::EPSILON {
+            return;
+        }
+        let (dir, from, to) = if from.1 < to.1 {
+            (1.0, from, to)
+        } else {
+            (-1.0, to, from)
+        };
+        let dxdy = (to.0 - from.0) / (to.1 - from.1);
+        let mut x = from.0;
+        let max_x = (self.width - 1) as f32;
+        for y in from.1.max(0.0) as usize..(to.1.ceil() as usize).min(self.height) {
+            let row = y * self.width;
+            let dy = ((y + 1) as f32).min(to.1) - (y as f32).max(from.1);
+            let next_x = x + dxdy * dy;
+            let d = dy * dir;
+            let (x0, x1) = if x < next_x { (x, next_x) } else { (next_x, x) };
+            let (x0, x1) = (x0.clamp(0.0, max_x), x1.clamp(0.0, max_x));
+            let x0_floor = x0.floor();
+            let x1_ceil = x1.ceil();
+            let (x0i, x1i) = (x0_floor as usize, x1_ceil as usize);
+            if x1i <= x0i + 1 {
+                let mid = 0.5 * (x0 + x1) - x0_floor;
+                self.area[row + x0i] += d - d * mid;
+                self.area[row + x0i + 1] += d * mid;
+            } else {
+                let s = (x1 - x0).recip();
+                let x0f = x0 - x0_floor;
+                let a0 = 0.5 * s * (1.0 - x0f) * (1.0 - x0f);
+                let x1f = x1 - x1_ceil + 1.0;
+                let am = 0.5 * s * x1f * x1f;
+                self.area[row + x0i] += d * a0;
+                if x1i == x0i + 2 {
+                    self.area[row + x0i + 1] += d * (1.0 - a0 - am);
+                } else {
+                    let a1 = s * (1.5 - x0f);
+                    self.area[row + x0i + 1] += d * (a1 - a0);
+                    for xi in x0i + 2..x1i - 1 {
+                        self.area[row + xi] += d * s;
+                    }
+                    let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+                    self.area[row + x1i - 1] += d * (1.0 - a2 - am);
+                }
+                self.area[row + x1i] += d * am;
+            }
+            x = next_x;
+        }
+    }
+
+    fn coverage(&self) -> Vec<f32> {
+        let mut sum = 0.0;
+        self.area[..self.width * self.height]
+            .iter()
+            .map(|area| {
+                sum += area;
+                sum.abs().min(1.0)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dejavu() -> Font {
+        let data = include_bytes!("../tests/fonts/DejaVuSansMono.ttf");
+        Font::parse(data.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn draws_glyphs() {
+        let font = dejavu();
+        assert_eq!(font.cell_width(20), 10);
+        let a = font.glyph('A', 20).unwrap();
+        assert_eq!(a.coverage.len(), a.width * a.height);
+        assert!((12..=16).contains(&a.height), "{}", a.height);
+        assert!(a.coverage.iter().all(|c| (0.0..=1.0).contains(c)));
+        // the crossbar is solid, the gap between the feet is empty
+        let row = |y: usize| &a.coverage[y * a.width..(y + 1) * a.width];
+        let bar = (0..a.height).max_by(|&y, &z| {
+            let sum = |y| row(y).iter().sum::<f32>();
+            sum(y).total_cmp(&sum(z))
+        });
+        assert!(bar.is_some_and(|y| y > a.height / 2));
+        let feet = row(a.height - 2);
+        assert!(feet[a.width / 2] < 0.1);
+        assert!(feet.iter().filter(|&&c| c > 0.1).count() >= 4);
+    }
+
+    #[test]
+    fn spaces_have_no_bitmap() {
+        let space = dejavu().glyph(' ', 20).unwrap();
+        assert_eq!((space.width, space.height), (0, 0));
+        assert!(dejavu().glyph('\u{10FFFD}', 20).is_none());
+    }
+
+    #[test]
+    fn rejects_what_isnt_a_font() {
+        assert!(Font::parse(b"not a font".to_vec()).is_err());
+    }
+
+    #[test]
+    fn says_where_it_looked() {
+        let err = find(&["/nowhere/Mono.ttf"]).unwrap_err().to_string();
+        assert!(err.contains("/nowhere/Mono.ttf"), "{err}");
+        assert!(err.ends_with("--png-font"), "{err}");
+    }
+}
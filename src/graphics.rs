@@ -0,0 +1,226 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::SystemTime,
+};
+
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ratatui::prelude::Rect;
+
+/// terminal graphics protocol used to draw images
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Protocol {
+    Kitty,
+    ITerm,
+    None,
+}
+
+/// how images are drawn, picked with `--image-mode`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum ImageMode {
+    /// a graphics protocol if the terminal has one, half blocks otherwise
+    #[default]
+    Auto,
+    /// only a graphics protocol, the alt text otherwise
+    Protocol,
+    /// colored half blocks, two pixels per cell
+    Blocks,
+    /// braille dots, finer but one color per cell
+    Braille,
+    /// always the alt text
+    Text,
+}
+
+pub(crate) fn protocol() -> Protocol {
+    static PROTOCOL: OnceLock<Protocol> = OnceLock::new();
+    *PROTOCOL.get_or_init(|| {
+        let term = std::env::var("TERM").unwrap_or_default();
+        let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+        if std::env::var_os("KITTY_WINDOW_ID").is_some() || term.contains("kitty") {
+            Protocol::Kitty
+        } else if term_program == "iTerm.app" || term_program == "WezTerm" {
+            Protocol::ITerm
+        } else {
+            Protocol::None
+        }
+    })
+}
+
+/// returns `true` if `path` can be drawn with the detected protocol
+pub(crate) fn supported(path: &Path) -> bool {
+    match protocol() {
+        // kitty's file transmission only decodes png itself
+        Protocol::Kitty => is_png(path),
+        Protocol::ITerm => path.is_file(),
+        Protocol::None => false,
+    }
+}
+
+fn is_png(path: &Path) -> bool {
+    let mut magic = [0u8; 8];
+    std::fs::File::open(path)
+        .and_then(|mut f| std::io::Read::read_exact(&mut f, &mut magic))
+        .is_ok()
+        && magic == *b"\x89PNG\r\n\x1a\n"
+}
+
+/// the pixel dimensions of a png, gif or jpeg file, read once and kept
+/// until the file changes since this runs for every drawn frame
+pub(crate) fn dimensions(path: &Path) -> Option<(u32, u32)> {
+    /// the dimensions of a file along with when it was modified
+    type Header = (Option<SystemTime>, Option<(u32, u32)>);
+    thread_local! {
+        static CACHE: RefCell<HashMap<PathBuf, Header>> = RefCell::new(HashMap::new());
+    }
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        match cache.get(path) {
+            Some(&(at, dims)) if at == modified => dims,
+            _ => {
+                let dims = read_dimensions(path);
+                cache.insert(path.to_owned(), (modified, dims));
+                dims
+            }
+        }
+    })
+}
+
+/// reads the pixel dimensions from the header of a png, gif or jpeg file
+fn read_dimensions(path: &Path) -> Option<(u32, u32)> {
+    let data = std::fs::read(path).ok()?;
+    let be16 = |i: usize| Some(u16::from_be_bytes([*data.get(i)?, *data.get(i + 1)?]) as u32);
+    let be32 = |i: usize| Some(u32::from_be_bytes(data.get(i..i + 4)?.try_into().ok()?));
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(16)?, be32(20)?));
+    }
+    if data.starts_with(b"GIF8") {
+        let le16 = |i: usize| Some(u16::from_le_bytes([*data.get(i)?, *data.get(i + 1)?]) as u32);
+        return Some((le16(6)?, le16(8)?));
+    }
+    if data.starts_with(&[0xff, 0xd8]) {
+        // walk the jpeg segments until a start-of-frame marker
+        let mut i = 2;
+        while i + 9 < data.len() {
+            if data[i] != 0xff {
+                return None;
+            }
+            let marker = data[i + 1];
+            if (0xc0..=0xcf).contains(&marker) && ![0xc4, 0xc8, 0xcc].contains(&marker) {
+                return Some((be16(i + 7)?, be16(i + 5)?));
+            }
+            i += 2 + be16(i + 2)? as usize;
+        }
+    }
+    None
+}
+
+/// largest rect inside `area` keeping the image aspect ratio,
+/// assuming terminal cells are twice as tall as they are wide
+pub(crate) fn fit(path: &Path, area: Rect) -> Rect {
+    let Some((w, h)) = dimensions(path).filter(|&(w, h)| w > 0 && h > 0) else {
+        return area;
+    };
+    let (w, h) = (w as u64, h as u64);
+    let max_cols = area.width as u64;
+    let max_rows = area.height as u64;
+    let cols = max_cols.min(max_rows * 2 * w / h).max(1);
+    let rows = (cols * h / (2 * w)).clamp(1, max_rows.max(1));
+    Rect {
+        width: cols as u16,
+        height: rows as u16,
+        ..area
+    }
+}
+
+thread_local! {
+    static QUEUED: RefCell<Vec<(PathBuf, Rect)>> = const { RefCell::new(vec![]) };
+    static SHOWN: RefCell<HashSet<(PathBuf, Rect)>> = RefCell::new(HashSet::new());
+}
+
+/// remember an image to be drawn at `rect` once the frame has been flushed
+pub(crate) fn queue(path: &Path, rect: Rect) {
+    QUEUED.with(|q| q.borrow_mut().push((path.to_owned(), rect)));
+}
+
+/// how many images are queued, to tell the ones queued after apart
+pub(crate) fn queued() -> usize {
+    QUEUED.with(|q| q.borrow().len())
+}
+
+/// moves the images queued after the first `from` up `rows` rows, dropping
+/// those that are no longer fully inside `visible`
+pub(crate) fn scroll(from: usize, rows: u16, visible: Rect) {
+    QUEUED.with(|q| {
+        let mut q = q.borrow_mut();
+        let from = from.min(q.len());
+        let moved = q
+            .split_off(from)
+            .into_iter()
+            .filter(|(_, rect)| rect.y >= visible.y + rows)
+            .map(|(path, rect)| {
+                let rect = Rect {
+                    y: rect.y - rows,
+                    ..rect
+                };
+                (path, rect)
+            })
+            .filter(|(_, rect)| visible.union(*rect) == visible)
+            .collect::<Vec<_>>();
+        q.extend(moved);
+    });
+}
+
+/// remove all visible images, returns `true` if the screen needs a full redraw
+pub(crate) fn clear(out: &mut impl Write) -> Result<bool> {
+    QUEUED.with(|q| q.borrow_mut().clear());
+    let shown = SHOWN.with(|s| std::mem::take(&mut *s.borrow_mut()));
+    if shown.is_empty() {
+        return Ok(false);
+    }
+    if protocol() == Protocol::Kitty {
+        write!(out, "\x1b_Ga=d,q=2\x1b\\")?;
+    }
+    Ok(true)
+}
+
+/// write the queued images to the terminal, skipping those already visible
+/// and those that can't be read, which keep showing their alt text
+pub(crate) fn flush(out: &mut impl Write) -> Result<()> {
+    let queued = QUEUED.with(|q| std::mem::take(&mut *q.borrow_mut()));
+    for (path, rect) in queued {
+        if SHOWN.with(|s| s.borrow().contains(&(path.clone(), rect))) {
+            continue;
+        }
+        let image = match protocol() {
+            Protocol::Kitty => path.canonicalize().ok().map(|full| {
+                format!(
+                    "\x1b_Ga=T,t=f,f=100,c={},r={},C=1,q=2;{}\x1b\\",
+                    rect.width,
+                    rect.height,
+                    STANDARD.encode(full.to_string_lossy().as_bytes())
+                )
+            }),
+            Protocol::ITerm => std::fs::read(&path).ok().map(|data| {
+                format!(
+                    "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+                    data.len(),
+                    rect.width,
+                    rect.height,
+                    STANDARD.encode(&data)
+                )
+            }),
+            Protocol::None => None,
+        };
+        let Some(image) = image else {
+            continue;
+        };
+        write!(out, "\x1b[{};{}H{image}", rect.y + 1, rect.x + 1)?;
+        SHOWN.with(|s| s.borrow_mut().insert((path, rect)));
+    }
+    Ok(out.flush()?)
+}
@@ -0,0 +1,103 @@
+use std::sync::OnceLock;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{FontStyle, Theme, ThemeSet},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+/// background the code is drawn on, picks a syntax theme that stays readable on it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Background {
+    Light,
+    #[default]
+    Dark,
+}
+
+impl Background {
+    /// guesses the terminal background from `COLORFGBG`, which many terminals
+    /// set to `fg;bg` color indices, dark if it isn't set
+    pub(crate) fn detect() -> Self {
+        let bg = std::env::var("COLORFGBG").ok().and_then(|colors| {
+            let (_, bg) = colors.rsplit_once(';')?;
+            bg.parse::<u8>().ok()
+        });
+        match bg {
+            // white, light gray and the bright colors except for dark gray
+            Some(7 | 9..=15) => Background::Light,
+            _ => Background::Dark,
+        }
+    }
+
+    fn theme_name(self) -> &'static str {
+        match self {
+            Background::Light => "base16-ocean.light",
+            Background::Dark => "base16-ocean.dark",
+        }
+    }
+}
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme(background: Background) -> &'static Theme {
+    static THEMES: OnceLock<[Theme; 2]> = OnceLock::new();
+    let [light, dark] = THEMES.get_or_init(|| {
+        let mut themes = ThemeSet::load_defaults().themes;
+        [Background::Light, Background::Dark]
+            .map(|bg| themes.remove(bg.theme_name()).unwrap_or_default())
+    });
+    match background {
+        Background::Light => light,
+        Background::Dark => dark,
+    }
+}
+
+/// highlights `src` as `lang` for a terminal with a `background` background,
+/// `None` if the language is unknown
+pub(crate) fn highlight(lang: &str, src: &str, background: Background) -> Option<Text<'static>> {
+    if lang.is_empty() {
+        return None;
+    }
+    let syntaxes = syntax_set();
+    let syntax = syntaxes.find_syntax_by_token(lang)?;
+    let mut highlighter = HighlightLines::new(syntax, theme(background));
+    let mut lines = vec![];
+    for line in LinesWithEndings::from(src) {
+        let ranges = highlighter.highlight_line(line, syntaxes).ok()?;
+        lines.push(Line::from(
+            ranges
+                .into_iter()
+                .map(|(style, text)| {
+                    Span::styled(
+                        text.trim_end_matches(['\n', '\r']).to_owned(),
+                        convert(style),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ));
+    }
+    Some(Text::from(lines))
+}
+
+fn convert(style: syntect::highlighting::Style) -> Style {
+    let fg = style.foreground;
+    let mut out = Style::default().fg(Color::Rgb(fg.r, fg.g, fg.b));
+    if style.font_style.contains(FontStyle::BOLD) {
+        out = out.add_modifier(Modifier::BOLD);
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        out = out.add_modifier(Modifier::ITALIC);
+    }
+    if style.font_style.contains(FontStyle::UNDERLINE) {
+        out = out.add_modifier(Modifier::UNDERLINED);
+    }
+    out
+}
@@ -0,0 +1,114 @@
+use std::{cell::RefCell, io::Write};
+
+use anyhow::Result;
+use ratatui::{
+    prelude::Rect,
+    style::{Color, Modifier, Style},
+};
+
+thread_local! {
+    static QUEUED: RefCell<Vec<(String, String, Rect, Style)>> = const { RefCell::new(vec![]) };
+}
+
+/// remember `text` drawn at `rect` to be turned into a link to `url` once the frame is flushed
+pub(crate) fn queue(url: &str, text: &str, rect: Rect, style: Style) {
+    QUEUED.with(|q| {
+        q.borrow_mut()
+            .push((url.to_owned(), text.to_owned(), rect, style))
+    });
+}
+
+/// how many links are queued, to tell the ones queued after apart
+pub(crate) fn queued() -> usize {
+    QUEUED.with(|q| q.borrow().len())
+}
+
+/// moves the links queued after the first `from` up `rows` rows, dropping
+/// those that are no longer fully inside `visible`
+pub(crate) fn scroll(from: usize, rows: u16, visible: Rect) {
+    QUEUED.with(|q| {
+        let mut q = q.borrow_mut();
+        let from = from.min(q.len());
+        let moved = q
+            .split_off(from)
+            .into_iter()
+            .filter(|(_, _, rect, _)| rect.y >= visible.y + rows)
+            .map(|(url, text, rect, style)| {
+                let rect = Rect {
+                    y: rect.y - rows,
+                    ..rect
+                };
+                (url, text, rect, style)
+            })
+            .filter(|(_, _, rect, _)| visible.union(*rect) == visible)
+            .collect::<Vec<_>>();
+        q.extend(moved);
+    });
+}
+
+/// forget the queued links, for frames where something is drawn over them
+pub(crate) fn discard() {
+    QUEUED.with(|q| q.borrow_mut().clear());
+}
+
+/// rewrites the queued text wrapped in OSC 8 escapes, terminals without
+/// hyperlink support ignore those and keep showing the styled text
+pub(crate) fn flush(out: &mut impl Write) -> Result<()> {
+    let queued = QUEUED.with(|q| std::mem::take(&mut *q.borrow_mut()));
+    for (url, text, rect, style) in queued {
+        write!(
+            out,
+            "\x1b[{};{}H\x1b[{}m\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\\x1b[0m",
+            rect.y + 1,
+            rect.x + 1,
+            sgr(style)
+        )?;
+    }
+    Ok(out.flush()?)
+}
+
+/// select graphic rendition parameters for the colors and text attributes of `style`
+fn sgr(style: Style) -> String {
+    let modifier = style.add_modifier - style.sub_modifier;
+    [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::CROSSED_OUT, "9"),
+    ]
+    .into_iter()
+    .filter(|(m, _)| modifier.contains(*m))
+    .map(|(_, code)| code.to_string())
+    .chain(style.fg.map(|fg| color(fg, 30)))
+    .chain(style.bg.map(|bg| color(bg, 40)))
+    .collect::<Vec<_>>()
+    .join(";")
+}
+
+/// the parameters selecting `color`, `base` is 30 for the foreground and 40 for the background
+fn color(color: Color, base: u8) -> String {
+    let named = |n: u8| (base + n).to_string();
+    match color {
+        Color::Reset => named(9),
+        Color::Black => named(0),
+        Color::Red => named(1),
+        Color::Green => named(2),
+        Color::Yellow => named(3),
+        Color::Blue => named(4),
+        Color::Magenta => named(5),
+        Color::Cyan => named(6),
+        Color::Gray => named(7),
+        // the bright variants start 60 further up
+        Color::DarkGray => named(60),
+        Color::LightRed => named(61),
+        Color::LightGreen => named(62),
+        Color::LightYellow => named(63),
+        Color::LightBlue => named(64),
+        Color::LightMagenta => named(65),
+        Color::LightCyan => named(66),
+        Color::White => named(67),
+        Color::Indexed(n) => format!("{};5;{n}", base + 8),
+        Color::Rgb(r, g, b) => format!("{};2;{r};{g};{b}", base + 8),
+    }
+}
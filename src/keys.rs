@@ -0,0 +1,291 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use crossterm::event::KeyCode;
+use serde::{Deserialize, Deserializer};
+
+/// something a key press does while presenting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Action {
+    Next,
+    Prev,
+    First,
+    Last,
+    /// move a slide too tall for the terminal up a row to see more of its bottom
+    ScrollDown,
+    ScrollUp,
+    /// start typing the id of a slide to jump to
+    Goto,
+    /// start typing text to look for
+    Search,
+    SearchNext,
+    SearchPrev,
+    /// show all slides as a grid to pick one from
+    Overview,
+    /// show a code block, image, qr code or table of the slide on its own
+    Zoom,
+    /// put the code of the slide on the clipboard
+    CopyCode,
+    ToggleTimer,
+    ResetTimer,
+    /// hide the slide behind a black screen until pressed again
+    BlackScreen,
+    WhiteScreen,
+    Help,
+    Quit,
+}
+
+impl Action {
+    fn describe(self) -> &'static str {
+        match self {
+            Action::Next => "next slide",
+            Action::Prev => "previous slide",
+            Action::First => "first slide",
+            Action::Last => "last slide",
+            Action::ScrollDown => "scroll down a slide that doesn't fit",
+            Action::ScrollUp => "scroll up a slide that doesn't fit",
+            Action::Goto => "go to the slide with an id",
+            Action::Search => "search the slides",
+            Action::SearchNext => "next search match",
+            Action::SearchPrev => "previous search match",
+            Action::Overview => "pick a slide from an overview",
+            Action::Zoom => "zoom into an item, j and k pick another",
+            Action::CopyCode => "copy the code of the slide, or the zoomed block",
+            Action::ToggleTimer => "show or hide the timer",
+            Action::ResetTimer => "restart the timer",
+            Action::BlackScreen => "black out the screen",
+            Action::WhiteScreen => "white out the screen",
+            Action::Help => "show or hide this help",
+            Action::Quit => "quit",
+        }
+    }
+}
+
+/// keys bound to each action, loaded from `<config dir>/mkslides/keys.yaml`
+///
+/// keys are written like `l`, `space`, `right` or `pagedown`, any action
+/// left out keeps its default keys
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub(crate) struct Keymap {
+    #[serde(deserialize_with = "keys")]
+    next: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    prev: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    first: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    last: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    scroll_down: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    scroll_up: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    goto: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    search: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    search_next: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    search_prev: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    overview: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    zoom: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    copy_code: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    toggle_timer: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    reset_timer: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    black_screen: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    white_screen: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    help: Vec<KeyCode>,
+    #[serde(deserialize_with = "keys")]
+    quit: Vec<KeyCode>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            // clickers send page down and page up
+            next: vec![
+                KeyCode::Char('l'),
+                KeyCode::Right,
+                KeyCode::Char(' '),
+                KeyCode::PageDown,
+            ],
+            prev: vec![
+                KeyCode::Char('h'),
+                KeyCode::Left,
+                KeyCode::Backspace,
+                KeyCode::PageUp,
+            ],
+            first: vec![KeyCode::Char('g')],
+            last: vec![KeyCode::Char('G')],
+            scroll_down: vec![KeyCode::Char('j'), KeyCode::Down],
+            scroll_up: vec![KeyCode::Char('k'), KeyCode::Up],
+            goto: vec![KeyCode::Char(':')],
+            search: vec![KeyCode::Char('/')],
+            search_next: vec![KeyCode::Char('n')],
+            search_prev: vec![KeyCode::Char('N')],
+            overview: vec![KeyCode::Char('o'), KeyCode::Tab],
+            zoom: vec![KeyCode::Char('z')],
+            copy_code: vec![KeyCode::Char('y')],
+            toggle_timer: vec![KeyCode::Char('t')],
+            reset_timer: vec![KeyCode::Char('r')],
+            black_screen: vec![KeyCode::Char('b')],
+            white_screen: vec![KeyCode::Char('w')],
+            help: vec![KeyCode::Char('?')],
+            quit: vec![KeyCode::Char('q'), KeyCode::Esc],
+        }
+    }
+}
+
+impl Keymap {
+    pub(crate) fn path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("mkslides").join("keys.yaml"))
+    }
+
+    /// reads the keymap from the config dir, the defaults are used if there is none
+    pub(crate) fn load() -> Result<Self> {
+        let Some(path) = Self::path().filter(|p| p.exists()) else {
+            return Ok(Self::default());
+        };
+        let src = std::fs::read_to_string(&path)
+            .with_context(|| format!("could not read keymap '{}'", path.display()))?;
+        serde_yaml::from_str(&src).with_context(|| format!("invalid keymap '{}'", path.display()))
+    }
+
+    fn bindings(&self) -> [(Action, &[KeyCode]); 19] {
+        [
+            (Action::Next, &self.next),
+            (Action::Prev, &self.prev),
+            (Action::First, &self.first),
+            (Action::Last, &self.last),
+            (Action::ScrollDown, &self.scroll_down),
+            (Action::ScrollUp, &self.scroll_up),
+            (Action::Goto, &self.goto),
+            (Action::Search, &self.search),
+            (Action::SearchNext, &self.search_next),
+            (Action::SearchPrev, &self.search_prev),
+            (Action::Overview, &self.overview),
+            (Action::Zoom, &self.zoom),
+            (Action::CopyCode, &self.copy_code),
+            (Action::ToggleTimer, &self.toggle_timer),
+            (Action::ResetTimer, &self.reset_timer),
+            (Action::BlackScreen, &self.black_screen),
+            (Action::WhiteScreen, &self.white_screen),
+            (Action::Help, &self.help),
+            (Action::Quit, &self.quit),
+        ]
+    }
+
+    pub(crate) fn action(&self, code: KeyCode) -> Option<Action> {
+        self.bindings()
+            .into_iter()
+            .find(|(_, keys)| keys.contains(&code))
+            .map(|(action, _)| action)
+    }
+
+    /// one row per action listing all of its keys, the help overlay shows these
+    pub(crate) fn help(&self) -> Vec<(String, &'static str)> {
+        let mut rows = self
+            .bindings()
+            .into_iter()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(action, keys)| {
+                let keys = keys.iter().map(|&key| key_name(key)).collect::<Vec<_>>();
+                (keys.join(", "), action.describe())
+            })
+            .collect::<Vec<_>>();
+        // digits aren't bindings of their own, they build up a number
+        rows.push(("<n> enter".into(), "go to slide n"));
+        rows
+    }
+}
+
+/// keys with a name, everything else is written as the character itself
+const NAMED: &[(&str, KeyCode)] = &[
+    ("space", KeyCode::Char(' ')),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("backspace", KeyCode::Backspace),
+    ("tab", KeyCode::Tab),
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+];
+
+fn key_name(code: KeyCode) -> String {
+    match NAMED.iter().find(|(_, key)| *key == code) {
+        Some((name, _)) => (*name).into(),
+        None => match code {
+            KeyCode::Char(c) => c.into(),
+            code => format!("{code:?}").to_lowercase(),
+        },
+    }
+}
+
+fn parse_key(src: &str) -> Option<KeyCode> {
+    let mut chars = src.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(KeyCode::Char(c)),
+        _ => NAMED
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(src))
+            .map(|&(_, key)| key),
+    }
+}
+
+fn keys<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<KeyCode>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|src| {
+            parse_key(src).ok_or_else(|| serde::de::Error::custom(format!("unknown key '{src}'")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keys() {
+        assert_eq!(parse_key("l"), Some(KeyCode::Char('l')));
+        assert_eq!(parse_key("G"), Some(KeyCode::Char('G')));
+        assert_eq!(parse_key("PageDown"), Some(KeyCode::PageDown));
+        assert_eq!(parse_key("space"), Some(KeyCode::Char(' ')));
+        assert_eq!(parse_key("f13"), None);
+        for (name, key) in NAMED {
+            assert_eq!(parse_key(&key_name(*key)), Some(*key), "{name}");
+        }
+    }
+
+    #[test]
+    fn loads_keymaps() {
+        let keymap: Keymap = serde_yaml::from_str("next: [enter, '5']\nquit: []").unwrap();
+        assert_eq!(keymap.action(KeyCode::Enter), Some(Action::Next));
+        assert_eq!(keymap.action(KeyCode::Char('5')), Some(Action::Next));
+        assert_eq!(keymap.action(KeyCode::Char('l')), None);
+        assert_eq!(keymap.action(KeyCode::Char('q')), None);
+        // actions left out keep their defaults
+        assert_eq!(keymap.action(KeyCode::Char('h')), Some(Action::Prev));
+        assert!(keymap.help().iter().all(|(_, what)| *what != "quit"));
+    }
+
+    #[test]
+    fn unknown_keys_are_errors() {
+        let err = serde_yaml::from_str::<Keymap>("next: [control-x]").unwrap_err();
+        assert!(err.to_string().contains("unknown key 'control-x'"), "{err}");
+    }
+}
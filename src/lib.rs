@@ -0,0 +1,40 @@
+//! very early stage cli slideshows
+//!
+//! the binary is a thin wrapper around [`cli`], other terminal apps can parse a
+//! deck with [`mkslides`] or [`mkslides_from_str`], or build one with
+//! [`SlideBuilder`], and show it with [`run`]
+//!
+//! [`Slides`], [`Slide`], [`SlideItem`] and [`ParseOptions`] and the functions
+//! above are the stable part of the api, the fields of the items are kept
+//! private so the markdown they come from can grow without breaking anyone
+
+mod ansi;
+mod app;
+mod art;
+mod builder;
+mod check;
+mod clipboard;
+mod emoji;
+mod export;
+mod fence;
+mod font;
+mod graphics;
+mod highlight;
+mod hyperlink;
+mod keys;
+mod math;
+mod pdf;
+mod raster;
+mod resume;
+mod slide;
+mod theme;
+mod transition;
+mod watch;
+mod wrap;
+
+pub use app::{cli, run};
+pub use builder::SlideBuilder;
+pub use slide::{
+    mkslides, mkslides_from_str, render_slide_to_buffer, Bullet, CodeBlock, HtmlMode, ParseOptions,
+    QrBlock, Run, Slide, SlideItem, Slides,
+};
@@ -0,0 +1,396 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::fence::Fences;
+
+/// best effort unicode rendering of a latex math expression,
+/// `None` if it uses something that can't be written that way
+pub(crate) fn unicode(src: &str) -> Option<String> {
+    let mut parser = Parser {
+        chars: src.trim().chars().peekable(),
+    };
+    let out = parser.expr(false)?;
+    // a stray `}` stops the expression early
+    parser.chars.peek().is_none().then_some(out)
+}
+
+/// replaces every `$...$` span in `text` with its unicode rendering,
+/// spans that can't be rendered are left as they are
+pub(crate) fn inline(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        // like pandoc, `$5 and $6` isn't math since the inside starts or ends with a
+        // space, and neither is `$5-$10` since the closing `$` is followed by a digit
+        let span = after
+            .find('$')
+            .filter(|&end| !after[end + 1..].starts_with(|c: char| c.is_ascii_digit()))
+            .map(|end| &after[..end])
+            .filter(|span| !span.is_empty() && span.trim() == *span);
+        match span.and_then(|span| Some((span, unicode(span)?))) {
+            Some((span, math)) => {
+                out.push_str(&math);
+                rest = &after[span.len() + 1..];
+            }
+            None => {
+                out.push('$');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// turns `$$ ... $$` blocks into ```` ```math ```` fences so markdown leaves their insides alone
+pub(crate) fn fence_display(src: &str) -> String {
+    let mut out = String::new();
+    let mut fences = Fences::default();
+    let mut in_math = false;
+    for line in src.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if in_math {
+            if let Some(body) = trimmed.strip_suffix("$$") {
+                push_line(&mut out, body);
+                out.push_str("```\n");
+                in_math = false;
+            } else {
+                out.push_str(line);
+            }
+        } else if fences.line(line) {
+            out.push_str(line);
+        } else if let Some(body) = trimmed.strip_prefix("$$") {
+            out.push_str("```math\n");
+            match body.strip_suffix("$$") {
+                Some(body) => {
+                    push_line(&mut out, body);
+                    out.push_str("```\n");
+                }
+                None => {
+                    push_line(&mut out, body);
+                    in_math = true;
+                }
+            }
+        } else {
+            out.push_str(line);
+        }
+    }
+    if in_math {
+        out.push_str("```\n");
+    }
+    out
+}
+
+fn push_line(out: &mut String, line: &str) {
+    if !line.trim().is_empty() {
+        out.push_str(line.trim());
+        out.push('\n');
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    /// everything up to the end, or up to the closing `}` of a group
+    fn expr(&mut self, group: bool) -> Option<String> {
+        let mut out = String::new();
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '}' if group => {
+                    self.chars.next();
+                    return Some(out);
+                }
+                '}' => return Some(out),
+                '^' | '_' => {
+                    self.chars.next();
+                    let script = self.atom()?;
+                    let table = if c == '^' { SUPERSCRIPTS } else { SUBSCRIPTS };
+                    // not every letter has a small version, keep the marker then
+                    match script_chars(&script, table) {
+                        Some(script) => out.push_str(&script),
+                        None => out.push_str(&format!("{c}{}", parens(&script))),
+                    }
+                }
+                _ => out.push_str(&self.atom()?),
+            }
+        }
+        // a group that never closed
+        (!group).then_some(out)
+    }
+
+    /// a single character, a `{group}` or a `\command` with its arguments
+    fn atom(&mut self) -> Option<String> {
+        match self.chars.next()? {
+            '{' => self.expr(true),
+            '\\' => self.command(),
+            c => Some(c.into()),
+        }
+    }
+
+    /// the next argument, skipping the spaces before it
+    fn arg(&mut self) -> Option<String> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        self.atom()
+    }
+
+    fn command(&mut self) -> Option<String> {
+        let mut name = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+            name.push(c);
+        }
+        if name.is_empty() {
+            // a symbol like `\,` or `\{`
+            return match self.chars.next()? {
+                ',' | ';' | ':' | ' ' => Some(" ".into()),
+                '!' => Some(String::new()),
+                '\\' => Some("\n".into()),
+                c => Some(c.into()),
+            };
+        }
+        match name.as_str() {
+            "frac" => {
+                let (num, den) = (self.arg()?, self.arg()?);
+                Some(fraction(&num, &den))
+            }
+            "sqrt" => Some(format!("√{}", parens(&self.arg()?))),
+            "text" | "mathrm" | "mathit" | "mathbf" | "operatorname" => self.arg(),
+            "left" | "right" => Some(String::new()),
+            "quad" | "qquad" => Some(" ".into()),
+            "sin" | "cos" | "tan" | "log" | "ln" | "exp" | "lim" | "max" | "min" | "det" => {
+                Some(name)
+            }
+            name => SYMBOLS
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, symbol)| (*symbol).into()),
+        }
+    }
+}
+
+/// wraps `src` in parentheses unless it reads as a single term
+fn parens(src: &str) -> String {
+    if src.chars().count() <= 1 || src.chars().all(char::is_alphanumeric) {
+        src.into()
+    } else {
+        format!("({src})")
+    }
+}
+
+fn fraction(num: &str, den: &str) -> String {
+    const VULGAR: &[(&str, &str, &str)] = &[
+        ("1", "2", "½"),
+        ("1", "3", "⅓"),
+        ("2", "3", "⅔"),
+        ("1", "4", "¼"),
+        ("3", "4", "¾"),
+        ("1", "5", "⅕"),
+        ("1", "6", "⅙"),
+        ("1", "8", "⅛"),
+    ];
+    match VULGAR.iter().find(|(n, d, _)| *n == num && *d == den) {
+        Some((_, _, vulgar)) => (*vulgar).into(),
+        None => format!("{}/{}", parens(num), parens(den)),
+    }
+}
+
+/// `src` in superscript characters, if every one of them has a small version
+pub(crate) fn superscript(src: &str) -> Option<String> {
+    script_chars(src, SUPERSCRIPTS)
+}
+
+fn script_chars(src: &str, table: &[(char, char)]) -> Option<String> {
+    src.chars()
+        .map(|c| table.iter().find(|(from, _)| *from == c).map(|&(_, to)| to))
+        .collect()
+}
+
+const SUPERSCRIPTS: &[(char, char)] = &[
+    ('0', '⁰'),
+    ('1', '¹'),
+    ('2', '²'),
+    ('3', '³'),
+    ('4', '⁴'),
+    ('5', '⁵'),
+    ('6', '⁶'),
+    ('7', '⁷'),
+    ('8', '⁸'),
+    ('9', '⁹'),
+    ('+', '⁺'),
+    ('-', '⁻'),
+    ('=', '⁼'),
+    ('(', '⁽'),
+    (')', '⁾'),
+    ('a', 'ᵃ'),
+    ('b', 'ᵇ'),
+    ('c', 'ᶜ'),
+    ('d', 'ᵈ'),
+    ('e', 'ᵉ'),
+    ('f', 'ᶠ'),
+    ('g', 'ᵍ'),
+    ('h', 'ʰ'),
+    ('i', 'ⁱ'),
+    ('j', 'ʲ'),
+    ('k', 'ᵏ'),
+    ('l', 'ˡ'),
+    ('m', 'ᵐ'),
+    ('n', 'ⁿ'),
+    ('o', 'ᵒ'),
+    ('p', 'ᵖ'),
+    ('r', 'ʳ'),
+    ('s', 'ˢ'),
+    ('t', 'ᵗ'),
+    ('u', 'ᵘ'),
+    ('v', 'ᵛ'),
+    ('w', 'ʷ'),
+    ('x', 'ˣ'),
+    ('y', 'ʸ'),
+    ('z', 'ᶻ'),
+    ('T', 'ᵀ'),
+    ('′', '′'),
+];
+
+const SUBSCRIPTS: &[(char, char)] = &[
+    ('0', '₀'),
+    ('1', '₁'),
+    ('2', '₂'),
+    ('3', '₃'),
+    ('4', '₄'),
+    ('5', '₅'),
+    ('6', '₆'),
+    ('7', '₇'),
+    ('8', '₈'),
+    ('9', '₉'),
+    ('+', '₊'),
+    ('-', '₋'),
+    ('=', '₌'),
+    ('(', '₍'),
+    (')', '₎'),
+    ('a', 'ₐ'),
+    ('e', 'ₑ'),
+    ('h', 'ₕ'),
+    ('i', 'ᵢ'),
+    ('j', 'ⱼ'),
+    ('k', 'ₖ'),
+    ('l', 'ₗ'),
+    ('m', 'ₘ'),
+    ('n', 'ₙ'),
+    ('o', 'ₒ'),
+    ('p', 'ₚ'),
+    ('r', 'ᵣ'),
+    ('s', 'ₛ'),
+    ('t', 'ₜ'),
+    ('u', 'ᵤ'),
+    ('v', 'ᵥ'),
+    ('x', 'ₓ'),
+];
+
+const SYMBOLS: &[(&str, &str)] = &[
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ε"),
+    ("varepsilon", "ε"),
+    ("zeta", "ζ"),
+    ("eta", "η"),
+    ("theta", "θ"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("nu", "ν"),
+    ("xi", "ξ"),
+    ("pi", "π"),
+    ("rho", "ρ"),
+    ("sigma", "σ"),
+    ("tau", "τ"),
+    ("upsilon", "υ"),
+    ("phi", "φ"),
+    ("varphi", "φ"),
+    ("chi", "χ"),
+    ("psi", "ψ"),
+    ("omega", "ω"),
+    ("Gamma", "Γ"),
+    ("Delta", "Δ"),
+    ("Theta", "Θ"),
+    ("Lambda", "Λ"),
+    ("Xi", "Ξ"),
+    ("Pi", "Π"),
+    ("Sigma", "Σ"),
+    ("Phi", "Φ"),
+    ("Psi", "Ψ"),
+    ("Omega", "Ω"),
+    ("times", "×"),
+    ("cdot", "·"),
+    ("div", "÷"),
+    ("pm", "±"),
+    ("mp", "∓"),
+    ("leq", "≤"),
+    ("le", "≤"),
+    ("geq", "≥"),
+    ("ge", "≥"),
+    ("neq", "≠"),
+    ("ne", "≠"),
+    ("approx", "≈"),
+    ("equiv", "≡"),
+    ("sim", "∼"),
+    ("propto", "∝"),
+    ("infty", "∞"),
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("int", "∫"),
+    ("oint", "∮"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+    ("to", "→"),
+    ("rightarrow", "→"),
+    ("leftarrow", "←"),
+    ("Rightarrow", "⇒"),
+    ("Leftarrow", "⇐"),
+    ("iff", "⇔"),
+    ("mapsto", "↦"),
+    ("in", "∈"),
+    ("notin", "∉"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("cup", "∪"),
+    ("cap", "∩"),
+    ("emptyset", "∅"),
+    ("forall", "∀"),
+    ("exists", "∃"),
+    ("neg", "¬"),
+    ("land", "∧"),
+    ("lor", "∨"),
+    ("circ", "∘"),
+    ("prime", "′"),
+    ("ldots", "…"),
+    ("cdots", "⋯"),
+    ("dots", "…"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_spans() {
+        assert_eq!(inline("so $x^2$ grows"), "so x² grows");
+        assert_eq!(inline("$\\alpha + \\beta$"), "α + β");
+    }
+
+    #[test]
+    fn keeps_dollar_amounts() {
+        assert_eq!(inline("$5-$10"), "$5-$10");
+        assert_eq!(inline("$20/$30"), "$20/$30");
+        assert_eq!(inline("between $5 and $6"), "between $5 and $6");
+    }
+
+    #[test]
+    fn keeps_what_it_cant_render() {
+        assert_eq!(inline("a lone $ sign"), "a lone $ sign");
+        assert_eq!(inline("$$"), "$$");
+    }
+}
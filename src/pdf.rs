@@ -0,0 +1,190 @@
+use std::{fmt::Write as _, io::Write, path::Path};
+
+use anyhow::{Context, Result};
+use flate2::{write::ZlibEncoder, Compression};
+
+use crate::{
+    font::Font,
+    raster::{self, Image},
+    slide::{RenderOptions, Slides},
+};
+
+/// size of each pdf page, all of them landscape
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum PageSize {
+    /// 16:9, like a widescreen presentation
+    #[default]
+    Wide,
+    /// 4:3, like an old projector
+    Standard,
+    A4,
+    Letter,
+}
+
+impl PageSize {
+    /// width and height in points
+    fn points(self) -> (f32, f32) {
+        match self {
+            PageSize::Wide => (960.0, 540.0),
+            PageSize::Standard => (720.0, 540.0),
+            PageSize::A4 => (841.89, 595.28),
+            PageSize::Letter => (792.0, 612.0),
+        }
+    }
+}
+
+/// writes the deck to `out` as a pdf with one page per slide
+pub(crate) fn export_pdf(
+    slides: &mut Slides,
+    opts: &RenderOptions,
+    font: &Font,
+    size: PageSize,
+    out: &Path,
+) -> Result<()> {
+    let images = raster::render_deck(slides, opts, font)?;
+    let pdf = to_pdf(&images, size)?;
+    std::fs::write(out, pdf).with_context(|| format!("could not write '{}'", out.display()))
+}
+
+fn to_pdf(images: &[Image], size: PageSize) -> Result<Vec<u8>> {
+    let (width, height) = size.points();
+    let mut pdf = Pdf::default();
+    // objects 1 and 2 are the catalog and the page tree, pages follow in threes
+    let page_ids = (0..images.len()).map(|i| 3 + i * 3).collect::<Vec<_>>();
+    pdf.object(b"<< /Type /Catalog /Pages 2 0 R >>");
+    let kids = page_ids.iter().fold(String::new(), |mut kids, id| {
+        let _ = write!(kids, "{id} 0 R ");
+        kids
+    });
+    pdf.object(
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.trim_end(),
+            images.len()
+        )
+        .as_bytes(),
+    );
+    for (image, id) in images.iter().zip(page_ids) {
+        pdf.object(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width} {height}] \
+                 /Resources << /XObject << /Slide {} 0 R >> >> /Contents {} 0 R >>",
+                id + 1,
+                id + 2
+            )
+            .as_bytes(),
+        );
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(&image.pixels)?;
+        pdf.stream(
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+                 /BitsPerComponent 8 /Filter /FlateDecode",
+                image.width, image.height
+            ),
+            &encoder.finish()?,
+        );
+        // scale the slide to fit the page and center it, the rest is filled
+        // with the same background as the slide
+        let scale = (width / image.width as f32).min(height / image.height as f32);
+        let (w, h) = (image.width as f32 * scale, image.height as f32 * scale);
+        let [r, g, b] = raster::DEFAULT_BG.map(|c| c as f32 / 255.0);
+        let contents = format!(
+            "{r:.3} {g:.3} {b:.3} rg 0 0 {width} {height} re f\n\
+             q {w:.2} 0 0 {h:.2} {:.2} {:.2} cm /Slide Do Q\n",
+            (width - w) / 2.0,
+            (height - h) / 2.0
+        );
+        pdf.stream("", contents.as_bytes());
+    }
+    Ok(pdf.finish())
+}
+
+/// a pdf being written, objects are numbered in the order they're added
+#[derive(Default)]
+struct Pdf {
+    out: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl Pdf {
+    fn start_object(&mut self) {
+        if self.out.is_empty() {
+            // the binary comment tells tools the file isn't plain text
+            self.out.extend(b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n");
+        }
+        self.offsets.push(self.out.len());
+        let _ = writeln!(self.out, "{} 0 obj", self.offsets.len());
+    }
+
+    fn object(&mut self, body: &[u8]) {
+        self.start_object();
+        self.out.extend(body);
+        self.out.extend(b"\nendobj\n");
+    }
+
+    fn stream(&mut self, dict: &str, data: &[u8]) {
+        self.start_object();
+        let _ = write!(self.out, "<< {dict} /Length {} >>\nstream\n", data.len());
+        self.out.extend(data);
+        self.out.extend(b"\nendstream\nendobj\n");
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let xref = self.out.len();
+        let _ = write!(
+            self.out,
+            "xref\n0 {}\n0000000000 65535 f \n",
+            self.offsets.len() + 1
+        );
+        for offset in &self.offsets {
+            let _ = writeln!(self.out, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            self.out,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%EOF\n",
+            self.offsets.len() + 1
+        );
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xref_points_at_objects() {
+        let page = || Image {
+            width: 2,
+            height: 1,
+            pixels: vec![0; 6],
+        };
+        let pdf = to_pdf(&[page(), page()], PageSize::Wide).unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+        // everything from the xref table on is text, the streams before it aren't
+        let start = pdf.windows(10).rposition(|w| w == b"startxref\n").unwrap();
+        let tail = std::str::from_utf8(&pdf[start + 10..]).unwrap();
+        let xref = tail.trim_end_matches("\n%EOF\n").parse::<usize>().unwrap();
+        let text = std::str::from_utf8(&pdf[xref..]).unwrap();
+        let (count, table) = text
+            .strip_prefix("xref\n0 ")
+            .and_then(|table| table.split_once('\n'))
+            .unwrap();
+        let count = count.parse::<usize>().unwrap();
+        // the catalog, the page tree and three objects per page
+        assert_eq!(count, 1 + 2 + 2 * 3);
+        assert!(text.contains(&format!("/Size {count} ")));
+        let entries = table.as_bytes().chunks(20).take(count).collect::<Vec<_>>();
+        assert_eq!(entries[0], b"0000000000 65535 f \n");
+        for (id, entry) in entries.iter().enumerate().skip(1) {
+            let entry = std::str::from_utf8(entry).unwrap();
+            assert!(entry.ends_with(" 00000 n \n"), "{entry:?}");
+            let offset = entry[..10].parse::<usize>().unwrap();
+            assert!(
+                pdf[offset..].starts_with(format!("{id} 0 obj").as_bytes()),
+                "{id}"
+            );
+        }
+    }
+}
@@ -0,0 +1,412 @@
+use std::{collections::HashMap, io::Write, path::Path};
+
+use anyhow::{Context, Result};
+use flate2::{write::ZlibEncoder, Compression, Crc};
+use ratatui::{
+    buffer::{Buffer, Cell},
+    prelude::Rect,
+    style::Modifier,
+};
+
+use crate::{
+    font::{Font, Glyph},
+    slide::{render_offscreen, slide_area, RenderOptions, Slides},
+    theme::{blend, rgb},
+};
+
+/// size of the grid slides are drawn on before rasterizing, about 16:9 with
+/// cells twice as tall as they are wide
+const COLUMNS: u16 = 142;
+const ROWS: u16 = 40;
+/// pixel height of one row
+const LINE_HEIGHT: usize = 24;
+
+/// colors used where the slide keeps the terminal's own
+const DEFAULT_FG: [u8; 3] = [0xd0, 0xd0, 0xd0];
+pub(crate) const DEFAULT_BG: [u8; 3] = [0x1c, 0x1c, 0x1c];
+
+/// an rgb picture, three bytes per pixel
+pub(crate) struct Image {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) pixels: Vec<u8>,
+}
+
+/// draws every slide of the deck on a headless terminal and rasterizes it with `font`
+pub(crate) fn render_deck(
+    slides: &mut Slides,
+    opts: &RenderOptions,
+    font: &Font,
+) -> Result<Vec<Image>> {
+    let size = Rect::new(0, 0, COLUMNS, ROWS);
+    slides.paginate(slide_area(size, opts), opts);
+    let mut rasterizer = Rasterizer::new(font);
+    let mut images = vec![];
+    for idx in 0..slides.slides().len() {
+        slides.goto(idx);
+        let buffer = render_offscreen(slides, opts, None, None, size)?;
+        images.push(rasterizer.image(&buffer));
+    }
+    Ok(images)
+}
+
+/// writes every slide to `dir` as `slide-01.png`, `slide-02.png` and so on
+pub(crate) fn export_png(
+    slides: &mut Slides,
+    opts: &RenderOptions,
+    font: &Font,
+    dir: &Path,
+) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("could not create '{}'", dir.display()))?;
+    let images = render_deck(slides, opts, font)?;
+    let digits = images.len().to_string().len().max(2);
+    for (idx, image) in images.iter().enumerate() {
+        let path = dir.join(format!("slide-{:0digits$}.png", idx + 1));
+        std::fs::write(&path, encode_png(image)?)
+            .with_context(|| format!("could not write '{}'", path.display()))?;
+    }
+    Ok(())
+}
+
+struct Rasterizer<'a> {
+    font: &'a Font,
+    cell_width: usize,
+    glyphs: HashMap<char, Option<Glyph>>,
+}
+
+impl<'a> Rasterizer<'a> {
+    fn new(font: &'a Font) -> Self {
+        Self {
+            font,
+            cell_width: font.cell_width(LINE_HEIGHT),
+            glyphs: HashMap::new(),
+        }
+    }
+
+    fn image(&mut self, buffer: &Buffer) -> Image {
+        let area = buffer.area;
+        let mut image = Image {
+            width: area.width as usize * self.cell_width,
+            height: area.height as usize * LINE_HEIGHT,
+            pixels: vec![],
+        };
+        image.pixels = vec![0; image.width * image.height * 3];
+        // backgrounds first, wide characters spill over into the cell after them
+        for foreground in [false, true] {
+            for y in 0..area.height {
+                for x in 0..area.width {
+                    let cell = buffer.get(x, y);
+                    let left = x as usize * self.cell_width;
+                    let top = y as usize * LINE_HEIGHT;
+                    if foreground {
+                        self.cell(&mut image, cell, left, top);
+                    } else {
+                        let (w, h) = (self.cell_width, LINE_HEIGHT);
+                        fill(&mut image, left, top, w, h, colors(cell).1, 1.0);
+                    }
+                }
+            }
+        }
+        image
+    }
+
+    fn cell(&mut self, image: &mut Image, cell: &Cell, left: usize, top: usize) {
+        let (w, h) = (self.cell_width, LINE_HEIGHT);
+        let (fg, _) = colors(cell);
+        let Some(c) = cell.symbol.chars().next() else {
+            return;
+        };
+        // block and box characters are drawn as shapes so they line up across
+        // cells, qr codes are made of them
+        let font = self.font;
+        if !shape(image, c, left, top, w, h, fg) {
+            if let Some(glyph) = self.glyphs.entry(c).or_insert_with(|| font.glyph(c, h)) {
+                draw_glyph(image, glyph, left, top, fg);
+                // there's no bold font, thicken the regular one instead
+                if cell.modifier.contains(Modifier::BOLD) {
+                    draw_glyph(image, glyph, left + 1, top, fg);
+                }
+            }
+        }
+        let line = (h / 16).max(1);
+        if cell.modifier.contains(Modifier::UNDERLINED) {
+            fill(image, left, top + h - 2 * line, w, line, fg, 1.0);
+        }
+        if cell.modifier.contains(Modifier::CROSSED_OUT) {
+            fill(image, left, top + h / 2, w, line, fg, 1.0);
+        }
+    }
+}
+
+/// foreground and background of `cell` after its modifiers are applied
+fn colors(cell: &Cell) -> ([u8; 3], [u8; 3]) {
+    let mut fg = rgb(cell.fg, DEFAULT_FG);
+    let mut bg = rgb(cell.bg, DEFAULT_BG);
+    if cell.modifier.contains(Modifier::REVERSED) {
+        std::mem::swap(&mut fg, &mut bg);
+    }
+    if cell.modifier.contains(Modifier::DIM) {
+        fg = blend(bg, fg, 0.5);
+    }
+    (fg, bg)
+}
+
+fn draw_glyph(image: &mut Image, glyph: &Glyph, left: usize, top: usize, color: [u8; 3]) {
+    for gy in 0..glyph.height {
+        for gx in 0..glyph.width {
+            let coverage = glyph.coverage[gy * glyph.width + gx];
+            let x = left as i32 + glyph.left + gx as i32;
+            let y = top as i32 + glyph.top + gy as i32;
+            if coverage > 0.0 && x >= 0 && y >= 0 {
+                fill(image, x as usize, y as usize, 1, 1, color, coverage);
+            }
+        }
+    }
+}
+
+/// paints a rectangle, `alpha` of 1 covers what was there
+fn fill(image: &mut Image, x: usize, y: usize, w: usize, h: usize, color: [u8; 3], alpha: f32) {
+    for py in y..(y + h).min(image.height) {
+        for px in x..(x + w).min(image.width) {
+            let at = (py * image.width + px) * 3;
+            let pixel = &mut image.pixels[at..at + 3];
+            let old = [pixel[0], pixel[1], pixel[2]];
+            pixel.copy_from_slice(&blend(old, color, alpha));
+        }
+    }
+}
+
+/// draws block elements and box drawing lines, `false` for any other character
+fn shape(
+    image: &mut Image,
+    c: char,
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    color: [u8; 3],
+) -> bool {
+    let eighth_h = |n: usize| h * n / 8;
+    let eighth_w = |n: usize| w * n / 8;
+    match c {
+        '█' => fill(image, x, y, w, h, color, 1.0),
+        '▀' => fill(image, x, y, w, h / 2, color, 1.0),
+        '▔' => fill(image, x, y, w, eighth_h(1), color, 1.0),
+        // lower blocks, from one eighth to seven eighths
+        '▁'..='▇' => {
+            let n = c as usize - '▁' as usize + 1;
+            fill(image, x, y + h - eighth_h(n), w, eighth_h(n), color, 1.0)
+        }
+        // left blocks, from seven eighths down to one eighth
+        '▉'..='▏' => {
+            let n = '▏' as usize - c as usize + 1;
+            fill(image, x, y, eighth_w(n), h, color, 1.0)
+        }
+        '▐' => fill(image, x + w / 2, y, w - w / 2, h, color, 1.0),
+        '▕' => fill(image, x + w - eighth_w(1), y, eighth_w(1), h, color, 1.0),
+        '░' => fill(image, x, y, w, h, color, 0.25),
+        '▒' => fill(image, x, y, w, h, color, 0.5),
+        '▓' => fill(image, x, y, w, h, color, 0.75),
+        '▖'..='▟' => {
+            // quadrants as top left, top right, bottom left, bottom right
+            const QUADRANTS: [u8; 10] = [
+                0b0010, 0b0001, 0b1000, 0b1011, 0b1001, 0b1110, 0b1101, 0b0100, 0b0110, 0b0111,
+            ];
+            let bits = QUADRANTS[c as usize - '▖' as usize];
+            let (hw, hh) = (w / 2, h / 2);
+            for (bit, qx, qy, qw, qh) in [
+                (0b1000, x, y, hw, hh),
+                (0b0100, x + hw, y, w - hw, hh),
+                (0b0010, x, y + hh, hw, h - hh),
+                (0b0001, x + hw, y + hh, w - hw, h - hh),
+            ] {
+                if bits & bit != 0 {
+                    fill(image, qx, qy, qw, qh, color, 1.0);
+                }
+            }
+        }
+        _ => match BOX_LINES.iter().find(|(line, _)| *line == c) {
+            Some(&(_, arms)) => box_lines(image, arms, x, y, w, h, color),
+            None => return false,
+        },
+    }
+    true
+}
+
+/// weight of the left, up, right and down arms of box drawing characters,
+/// 1 is light, 2 heavy and 3 double
+const BOX_LINES: &[(char, [u8; 4])] = &[
+    ('─', [1, 0, 1, 0]),
+    ('━', [2, 0, 2, 0]),
+    ('│', [0, 1, 0, 1]),
+    ('┃', [0, 2, 0, 2]),
+    ('┌', [0, 0, 1, 1]),
+    ('┏', [0, 0, 2, 2]),
+    ('┐', [1, 0, 0, 1]),
+    ('┓', [2, 0, 0, 2]),
+    ('└', [0, 1, 1, 0]),
+    ('┗', [0, 2, 2, 0]),
+    ('┘', [1, 1, 0, 0]),
+    ('┛', [2, 2, 0, 0]),
+    ('├', [0, 1, 1, 1]),
+    ('┣', [0, 2, 2, 2]),
+    ('┤', [1, 1, 0, 1]),
+    ('┫', [2, 2, 0, 2]),
+    ('┬', [1, 0, 1, 1]),
+    ('┳', [2, 0, 2, 2]),
+    ('┴', [1, 1, 1, 0]),
+    ('┻', [2, 2, 2, 0]),
+    ('┼', [1, 1, 1, 1]),
+    ('╋', [2, 2, 2, 2]),
+    ('═', [3, 0, 3, 0]),
+    ('║', [0, 3, 0, 3]),
+    ('╔', [0, 0, 3, 3]),
+    ('╗', [3, 0, 0, 3]),
+    ('╚', [0, 3, 3, 0]),
+    ('╝', [3, 3, 0, 0]),
+    ('╠', [0, 3, 3, 3]),
+    ('╣', [3, 3, 0, 3]),
+    ('╦', [3, 0, 3, 3]),
+    ('╩', [3, 3, 3, 0]),
+    ('╬', [3, 3, 3, 3]),
+    ('╭', [0, 0, 1, 1]),
+    ('╮', [1, 0, 0, 1]),
+    ('╯', [1, 1, 0, 0]),
+    ('╰', [0, 1, 1, 0]),
+    ('╴', [1, 0, 0, 0]),
+    ('╵', [0, 1, 0, 0]),
+    ('╶', [0, 0, 1, 0]),
+    ('╷', [0, 0, 0, 1]),
+];
+
+fn box_lines(
+    image: &mut Image,
+    arms: [u8; 4],
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    color: [u8; 3],
+) {
+    let light = (w / 8).max(1);
+    let (cx, cy) = (x + w / 2, y + h / 2);
+    for (arm, weight) in arms.into_iter().enumerate() {
+        // each arm runs from the middle to its edge, overlapping the middle a bit
+        // so corners are closed
+        let offsets: &[isize] = match weight {
+            0 => continue,
+            3 => &[-(light as isize), light as isize],
+            _ => &[0],
+        };
+        let thick = if weight == 2 { light * 2 } else { light };
+        for &offset in offsets {
+            let (lx, ly) = (
+                (cx as isize + offset) as usize - thick / 2,
+                (cy as isize + offset) as usize - thick / 2,
+            );
+            match arm {
+                0 => fill(image, x, ly, cx - x + thick, thick, color, 1.0),
+                1 => fill(image, lx, y, thick, cy - y + thick, color, 1.0),
+                2 => fill(
+                    image,
+                    cx - thick / 2,
+                    ly,
+                    x + w - cx + thick / 2,
+                    thick,
+                    color,
+                    1.0,
+                ),
+                _ => fill(
+                    image,
+                    lx,
+                    cy - thick / 2,
+                    thick,
+                    y + h - cy + thick / 2,
+                    color,
+                    1.0,
+                ),
+            }
+        }
+    }
+}
+
+/// encodes `image` as an 8 bit rgb png
+pub(crate) fn encode_png(image: &Image) -> Result<Vec<u8>> {
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut header = vec![];
+    header.extend((image.width as u32).to_be_bytes());
+    header.extend((image.height as u32).to_be_bytes());
+    // 8 bits per channel, rgb, default compression, filter and no interlacing
+    header.extend([8, 2, 0, 0, 0]);
+    chunk(&mut out, b"IHDR", &header);
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    for row in image.pixels.chunks(image.width * 3) {
+        // each row starts with its filter type, none here
+        encoder.write_all(&[0])?;
+        encoder.write_all(row)?;
+    }
+    chunk(&mut out, b"IDAT", &encoder.finish()?);
+    chunk(&mut out, b"IEND", &[]);
+    Ok(out)
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    out.extend(kind);
+    out.extend(data);
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    out.extend(crc.sum().to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::ZlibDecoder;
+
+    use super::*;
+
+    /// the chunks of a png after its signature, as kind and data
+    fn chunks(png: &[u8]) -> Vec<(&[u8], &[u8])> {
+        let mut chunks = vec![];
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind, data) = (&rest[4..8], &rest[8..8 + len]);
+            let mut crc = Crc::new();
+            crc.update(kind);
+            crc.update(data);
+            assert_eq!(rest[8 + len..12 + len], crc.sum().to_be_bytes());
+            chunks.push((kind, data));
+            rest = &rest[12 + len..];
+        }
+        chunks
+    }
+
+    #[test]
+    fn encodes_png() {
+        let image = Image {
+            width: 3,
+            height: 2,
+            pixels: (0..18).collect(),
+        };
+        let png = encode_png(&image).unwrap();
+        assert_eq!(png[..8], *b"\x89PNG\r\n\x1a\n");
+        let chunks = chunks(&png);
+        let kinds = chunks.iter().map(|(kind, _)| *kind).collect::<Vec<_>>();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        let mut rows = vec![];
+        ZlibDecoder::new(chunks[1].1)
+            .read_to_end(&mut rows)
+            .unwrap();
+        let mut expected = vec![0];
+        expected.extend(0..9);
+        expected.push(0);
+        expected.extend(9..18);
+        assert_eq!(rows, expected);
+    }
+}
@@ -0,0 +1,54 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::{Context, Result};
+
+/// where `--resume` keeps the last slide of every deck, in `<cache dir>/mkslides/positions.yaml`
+///
+/// decks are keyed by the absolute paths of their files, the slide index
+/// counts slides before they are split into pages
+#[derive(Debug, Default)]
+pub(crate) struct Positions(BTreeMap<String, usize>);
+
+impl Positions {
+    fn path() -> Option<PathBuf> {
+        Some(dirs::cache_dir()?.join("mkslides").join("positions.yaml"))
+    }
+
+    /// reads the saved positions, none if the file is missing or unreadable
+    /// since losing them only means starting from the first slide
+    pub(crate) fn load() -> Self {
+        let positions = Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|src| serde_yaml::from_str(&src).ok());
+        Self(positions.unwrap_or_default())
+    }
+
+    pub(crate) fn get(&self, files: &[String]) -> Option<usize> {
+        self.0.get(&key(files)).copied()
+    }
+
+    /// remembers slide `idx` for the deck made of `files` and writes all positions back
+    pub(crate) fn save(mut self, files: &[String], idx: usize) -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        self.0.insert(key(files), idx);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("could not create '{}'", dir.display()))?;
+        }
+        std::fs::write(&path, serde_yaml::to_string(&self.0)?)
+            .with_context(|| format!("could not write '{}'", path.display()))
+    }
+}
+
+fn key(files: &[String]) -> String {
+    files
+        .iter()
+        .map(|file| {
+            std::fs::canonicalize(file)
+                .map_or_else(|_| file.clone(), |path| path.display().to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -1,297 +1,3821 @@
-use std::rc::Rc;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    rc::Rc,
+    str::FromStr,
+    time::Duration,
+};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use comrak::{
     arena_tree::NodeEdge,
-    nodes::{AstNode, NodeValue},
+    nodes::{ListType, NodeValue},
 };
 use itertools::Itertools;
 use ratatui::{
-    prelude::{Alignment, Rect},
+    backend::TestBackend,
+    buffer::Buffer,
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style, Stylize},
-    text::{Span, Spans, Text},
-    widgets::{self, Block, Borders, ListItem, Padding, Paragraph, Wrap},
-    Frame,
+    text::{Line, Span, Text},
+    widgets::{self, Block, Borders, ListItem, Wrap},
+    Frame, Terminal,
+};
+use serde::{Deserialize, Serialize};
+use time::{Time, UtcOffset};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    ansi,
+    art::{self, Art, Picture},
+    emoji,
+    fence::Fences,
+    graphics::{self, ImageMode},
+    highlight::{self, Background},
+    hyperlink, math,
+    theme::{color_name, contrast, Theme},
+    transition::Transition,
+    wrap::{expand_tabs, wrap, wrap_styled},
 };
 
+/// draws a slide, borrowing the deck it was made from
+pub(crate) type SlideRender<'a, B> = Box<dyn FnOnce(&mut Frame<B>) + 'a>;
+
+#[derive(Debug, Clone)]
+pub enum SlideItem {
+    /// the level from 1 for `#` to 6 for `######`, and the text
+    Heading(u8, Vec<Run>),
+    Paragraph(Vec<Run>),
+    Bullets(Vec<Bullet>),
+    Quote(String),
+    Code(CodeBlock),
+    QR(QrBlock),
+    Image {
+        path: PathBuf,
+        alt: String,
+    },
+    /// speaker notes, kept on the slide but never drawn on it
+    Notes(String),
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// a divider across the slide, from `***` or `___` since `---` splits slides
+    Rule,
+    /// latex from a `$$` block or a `math` fence
+    Math(String),
+    /// terms and their descriptions, from a `term` line followed by `: description`
+    DefList(Vec<(String, String)>),
+    /// the text of the `[^name]` footnotes referenced on the slide, in order
+    Footnotes(Vec<String>),
+}
+
+/// a stretch of inline text drawn the same way throughout
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub(crate) text: String,
+    /// emphasis from the enclosing `*`, `**` and `~~` markers
+    pub(crate) modifier: Modifier,
+    /// target of the link the text belongs to
+    pub(crate) link: Option<String>,
+    /// inline code from between backticks, which aren't kept in `text`
+    pub(crate) code: bool,
+}
+
+impl Run {
+    fn style(&self, theme: &Theme) -> Style {
+        let mut style = Style::default().add_modifier(self.modifier);
+        if self.code {
+            style = style.fg(theme.inline_code_fg).bg(theme.inline_code_bg);
+        }
+        match self.link {
+            Some(_) => style.underlined(),
+            None => style,
+        }
+    }
+}
+
+/// the text of `runs` without any styling
+pub(crate) fn plain(runs: &[Run]) -> String {
+    runs.iter().map(|run| run.text.as_str()).collect()
+}
+
+/// wraps `runs` into lines of `(text, index of the run it came from)`
+fn wrap_runs(runs: &[Run], width: usize) -> Vec<Vec<(String, usize)>> {
+    let pieces = runs
+        .iter()
+        .enumerate()
+        .map(|(idx, run)| (run.text.as_str(), idx))
+        .collect::<Vec<_>>();
+    wrap_styled(&pieces, width)
+}
+
+/// builds one wrapped line starting at `x`, `y`, and queues the links in it
+/// that are fully inside `visible`
+fn styled_line(
+    runs: &[Run],
+    line: &[(String, usize)],
+    x: u16,
+    y: u16,
+    visible: Rect,
+    base: Style,
+    theme: &Theme,
+) -> Line<'static> {
+    let mut col = x;
+    let mut spans = vec![];
+    for (piece, idx) in line {
+        let run = &runs[*idx];
+        let style = base.patch(run.style(theme));
+        let width = piece.width() as u16;
+        if let Some(url) = &run.link {
+            let at = Rect::new(col, y, width, 1);
+            if clip(at, visible) == at {
+                hyperlink::queue(url, piece, at, style);
+            }
+        }
+        col += width;
+        spans.push(Span::styled(piece.clone(), style));
+    }
+    Line::from(spans)
+}
+
+/// markdown for `runs`, moving whitespace out of the emphasis markers
+/// so they stay valid delimiters
+pub(crate) fn runs_to_markdown(runs: &[Run]) -> String {
+    runs.iter()
+        .map(|run| {
+            let mut markers = String::new();
+            if run.modifier.contains(Modifier::BOLD) {
+                markers.push_str("**");
+            }
+            if run.modifier.contains(Modifier::ITALIC) {
+                markers.push('*');
+            }
+            if run.modifier.contains(Modifier::CROSSED_OUT) {
+                markers.push_str("~~");
+            }
+            let text = if run.code {
+                code_span(&run.text)
+            } else {
+                run.text.clone()
+            };
+            let inner = text.trim();
+            let text = if markers.is_empty() || inner.is_empty() {
+                text.clone()
+            } else {
+                let start = text.len() - text.trim_start().len();
+                let end = text.trim_end().len();
+                let closing = markers.chars().rev().collect::<String>();
+                format!(
+                    "{}{markers}{inner}{closing}{}",
+                    &text[..start],
+                    &text[end..]
+                )
+            };
+            // a backslash at the end of a line makes it a hard break
+            let text = text.replace('\n', "\\\n");
+            match &run.link {
+                Some(url) => format!("[{text}]({url})"),
+                None => text,
+            }
+        })
+        .collect()
+}
+
+/// `code` between enough backticks that none inside end it early
+fn code_span(code: &str) -> String {
+    let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let ticks = "`".repeat(longest + 1);
+    // a space keeps backticks at either end apart from the delimiters
+    let pad = if code.starts_with('`') || code.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{ticks}{pad}{code}{pad}{ticks}")
+}
+
+/// a fenced code block with the options from its info string,
+/// like ```` ```rust,linenos {2,4-6} ````
+#[derive(Debug, Clone)]
+pub struct CodeBlock {
+    pub(crate) lang: String,
+    pub(crate) src: String,
+    /// from a `,linenos` suffix on the language
+    pub(crate) line_numbers: bool,
+    /// zero based indices of the lines picked out by the `{..}` spec
+    pub(crate) highlight: BTreeSet<usize>,
+    /// ranges in the spec that end before they start, like `{6-4}`
+    pub(crate) inverted: Vec<String>,
+}
+
+impl CodeBlock {
+    pub(crate) fn new(info: &str, src: &str) -> Self {
+        let (info, spec) = match info.split_once('{') {
+            Some((info, spec)) => (info.trim(), spec.trim_end().trim_end_matches('}')),
+            None => (info.trim(), ""),
+        };
+        let (lang, line_numbers) = match info.strip_suffix(",linenos") {
+            Some(lang) => (lang, true),
+            None => (info, false),
+        };
+        let lines = src.lines().count();
+        let mut highlight = BTreeSet::new();
+        let mut inverted = vec![];
+        for part in spec.split(',').map(str::trim) {
+            let (start, end) = part.split_once('-').unwrap_or((part, part));
+            if let (Ok(start), Ok(end)) =
+                (start.trim().parse::<usize>(), end.trim().parse::<usize>())
+            {
+                if end < start {
+                    inverted.push(part.to_string());
+                }
+                // capped at the source, the spec could ask for any number of lines
+                highlight.extend(start.max(1) - 1..end.min(lines));
+            }
+        }
+        Self {
+            lang: lang.into(),
+            src: src.into(),
+            line_numbers,
+            highlight,
+            inverted,
+        }
+    }
+
+    /// expands the tabs of the source to stops every `width` columns, the
+    /// escapes of terminal output take none
+    pub(crate) fn expand_tabs(&mut self, width: usize) {
+        self.src = if self.is_ansi() {
+            ansi::expand_tabs(&self.src, width)
+        } else {
+            expand_tabs(&self.src, width)
+        };
+    }
+
+    /// whether the source is terminal output whose color escapes are shown as colors
+    fn is_ansi(&self) -> bool {
+        self.lang == "ansi"
+    }
+
+    /// the source as it shows up on the slide, without escape sequences
+    pub(crate) fn shown(&self) -> Cow<'_, str> {
+        if self.is_ansi() {
+            Cow::Owned(ansi::strip(&self.src))
+        } else {
+            Cow::Borrowed(&self.src)
+        }
+    }
+
+    /// the info string this block was parsed from, normalized
+    fn info(&self) -> String {
+        let mut info = self.lang.clone();
+        if self.line_numbers {
+            info.push_str(",linenos");
+        }
+        if !self.highlight.is_empty() {
+            // collapse consecutive lines back into ranges
+            let mut ranges: Vec<(usize, usize)> = vec![];
+            for &line in &self.highlight {
+                match ranges.last_mut() {
+                    Some((_, end)) if *end + 1 == line => *end = line,
+                    _ => ranges.push((line, line)),
+                }
+            }
+            let spec = ranges
+                .iter()
+                .map(|&(start, end)| {
+                    if start == end {
+                        format!("{}", start + 1)
+                    } else {
+                        format!("{}-{}", start + 1, end + 1)
+                    }
+                })
+                .join(",");
+            info.push_str(&format!(" {{{spec}}}"));
+        }
+        info
+    }
+}
+
+/// a `qrcode` block with the options from its info string,
+/// like ```` ```qrcode ecc=H caption="scan me" ````
+#[derive(Debug, Clone)]
+pub struct QrBlock {
+    pub(crate) src: String,
+    pub(crate) ecc: qrcode::EcLevel,
+    /// shown below the code, a bare `caption` shows the encoded text
+    pub(crate) caption: Option<String>,
+}
+
+impl QrBlock {
+    fn new(info: &str, src: &str) -> Self {
+        let src = src.trim();
+        let mut ecc = qrcode::EcLevel::M;
+        let mut caption = None;
+        let mut rest = info.trim().trim_start_matches("qrcode");
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            let end = rest.find(|c: char| c == '=' || c.is_whitespace());
+            let (key, tail) = rest.split_at(end.unwrap_or(rest.len()));
+            let (value, tail) = match tail.strip_prefix('=') {
+                Some(tail) => match tail.strip_prefix('"') {
+                    Some(tail) => tail.split_once('"').unwrap_or((tail, "")),
+                    None => tail.split_at(tail.find(char::is_whitespace).unwrap_or(tail.len())),
+                },
+                None => ("", tail),
+            };
+            rest = tail;
+            match (key, value) {
+                ("ecc", "L" | "l") => ecc = qrcode::EcLevel::L,
+                ("ecc", "M" | "m") => ecc = qrcode::EcLevel::M,
+                ("ecc", "Q" | "q") => ecc = qrcode::EcLevel::Q,
+                ("ecc", "H" | "h") => ecc = qrcode::EcLevel::H,
+                ("caption", "") => caption = Some(src.to_owned()),
+                ("caption", value) => caption = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+        Self {
+            src: src.into(),
+            ecc,
+            caption,
+        }
+    }
+
+    /// the info string this block was parsed from, normalized
+    fn info(&self) -> String {
+        let mut info = "qrcode".to_owned();
+        match self.ecc {
+            qrcode::EcLevel::L => info.push_str(" ecc=L"),
+            qrcode::EcLevel::M => {}
+            qrcode::EcLevel::Q => info.push_str(" ecc=Q"),
+            qrcode::EcLevel::H => info.push_str(" ecc=H"),
+        }
+        match &self.caption {
+            Some(caption) if *caption == self.src => info.push_str(" caption"),
+            Some(caption) => info.push_str(&format!(" caption=\"{caption}\"")),
+            None => {}
+        }
+        info
+    }
+
+    pub(crate) fn encode(&self) -> Result<qrcode::QrCode, qrcode::types::QrError> {
+        qrcode::QrCode::with_error_correction_level(&self.src, self.ecc)
+    }
+}
+
 #[derive(Debug, Clone)]
-pub(crate) enum SlideItem {
-    Heading(String),
-    Paragraph(String),
-    Bullets(Vec<String>),
-    Code(String),
-    QR(String),
+pub struct Bullet {
+    /// nesting depth, 0 for the outermost list
+    pub(crate) depth: usize,
+    /// position in an ordered list, `None` for unordered ones
+    pub(crate) number: Option<usize>,
+    /// whether a task list item is ticked, `None` for plain items
+    pub(crate) checked: Option<bool>,
+    pub(crate) text: Vec<Run>,
+    /// not revealed yet, its rows are left blank
+    pub(crate) hidden: bool,
 }
 
+impl Bullet {
+    /// the marker with its indent, and the text wrapped to fit beside it in `width`
+    fn wrapped(&self, width: usize) -> (String, Vec<Vec<(String, usize)>>) {
+        let checkbox = self.checked.map(|checked| if checked { "☑" } else { "☐" });
+        let marker = match (self.number, checkbox) {
+            (Some(n), Some(checkbox)) => format!("{n}. {checkbox}"),
+            (Some(n), None) => format!("{n}."),
+            (None, Some(checkbox)) => checkbox.into(),
+            (None, None) => BULLET_GLYPHS[self.depth % BULLET_GLYPHS.len()].into(),
+        };
+        let marker = format!("{}{marker} ", "  ".repeat(self.depth));
+        let lines = wrap_runs(&self.text, width.saturating_sub(marker.width()));
+        (marker, lines)
+    }
+}
+
+/// bullet glyph for each nesting depth, repeating for deeper lists
+const BULLET_GLYPHS: [&str; 3] = ["-", "◦", "▪"];
+
 impl SlideItem {
-    fn render<B: ratatui::backend::Backend>(&self, frame: &mut Frame<B>, rect: Rect) -> u16 {
+    /// whether focus mode can blow the item up to fill the screen
+    fn zoomable(&self) -> bool {
+        matches!(
+            self,
+            SlideItem::Code(_)
+                | SlideItem::Image { .. }
+                | SlideItem::QR(_)
+                | SlideItem::Table { .. }
+        )
+    }
+
+    /// short name of the kind of item, as used by `--json`
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            SlideItem::Heading(..) => "heading",
+            SlideItem::Paragraph(_) => "paragraph",
+            SlideItem::Bullets(_) => "bullets",
+            SlideItem::Quote(_) => "quote",
+            SlideItem::Code(_) => "code",
+            SlideItem::QR(_) => "qrcode",
+            SlideItem::Image { .. } => "image",
+            SlideItem::Notes(_) => "notes",
+            SlideItem::Table { .. } => "table",
+            SlideItem::Rule => "rule",
+            SlideItem::Math(_) => "math",
+            SlideItem::DefList(_) => "definitions",
+            SlideItem::Footnotes(_) => "footnotes",
+        }
+    }
+
+    /// the text that inline content currently appends to
+    fn text_mut(&mut self) -> Option<&mut String> {
+        match self {
+            SlideItem::Quote(src) => Some(src),
+            SlideItem::Image { alt, .. } => Some(alt),
+            SlideItem::Table { headers, rows } => rows.last_mut().unwrap_or(headers).last_mut(),
+            _ => None,
+        }
+    }
+
+    /// the styled text that inline content currently appends to
+    fn runs_mut(&mut self) -> Option<&mut Vec<Run>> {
+        match self {
+            SlideItem::Heading(_, runs) | SlideItem::Paragraph(runs) => Some(runs),
+            SlideItem::Bullets(bullets) => bullets.last_mut().map(|b| &mut b.text),
+            _ => None,
+        }
+    }
+
+    /// appends inline text, keeping its style on items that can show one
+    fn push_text(&mut self, text: &str, modifier: Modifier, link: Option<&str>) {
+        if let Some(runs) = self.runs_mut() {
+            match runs.last_mut() {
+                Some(run)
+                    if run.modifier == modifier && run.link.as_deref() == link && !run.code =>
+                {
+                    run.text.push_str(text)
+                }
+                _ => runs.push(Run {
+                    text: text.into(),
+                    modifier,
+                    link: link.map(Into::into),
+                    code: false,
+                }),
+            }
+        } else if let Some(dst) = self.text_mut() {
+            dst.push_str(text);
+        }
+    }
+
+    /// appends inline code as a run of its own, or with its backticks on plain text
+    fn push_code(&mut self, code: &str, modifier: Modifier, link: Option<&str>) {
+        if let Some(runs) = self.runs_mut() {
+            runs.push(Run {
+                text: code.into(),
+                modifier,
+                link: link.map(Into::into),
+                code: true,
+            });
+        } else if let Some(dst) = self.text_mut() {
+            dst.push_str(&code_span(code));
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let fenced = |info: &str, src: &str| {
+            // the fence has to be longer than any backtick run inside
+            let mut fence = "```".to_string();
+            while src.contains(fence.as_str()) {
+                fence.push('`');
+            }
+            format!("{fence}{info}\n{}\n{fence}", src.trim_end_matches('\n'))
+        };
+        match self {
+            SlideItem::Heading(level, runs) => {
+                format!("{} {}", "#".repeat(*level as usize), runs_to_markdown(runs))
+            }
+            SlideItem::Paragraph(runs) => runs_to_markdown(runs),
+            SlideItem::Bullets(bullets) => {
+                // children are indented past their parent's marker
+                let mut widths: Vec<usize> = vec![];
+                bullets
+                    .iter()
+                    .map(|b| {
+                        widths.truncate(b.depth);
+                        let marker = match b.number {
+                            Some(n) => format!("{n}."),
+                            None => "-".into(),
+                        };
+                        let indent = " ".repeat(widths.iter().sum());
+                        widths.push(marker.len() + 1);
+                        let checkbox = match b.checked {
+                            Some(true) => "[x] ",
+                            Some(false) => "[ ] ",
+                            None => "",
+                        };
+                        format!("{indent}{marker} {checkbox}{}", runs_to_markdown(&b.text))
+                    })
+                    .join("\n")
+            }
+            SlideItem::Quote(src) => src.lines().map(|l| format!("> {l}")).join("\n>\n"),
+            SlideItem::Code(code) => fenced(&code.info(), &code.src),
+            SlideItem::QR(qr) => fenced(&qr.info(), &qr.src),
+            SlideItem::Image { path, alt } => format!("![{alt}]({})", path.display()),
+            SlideItem::Notes(src) => fenced("notes", src),
+            SlideItem::Math(src) => fenced("math", src),
+            SlideItem::Rule => "***".into(),
+            SlideItem::DefList(entries) => entries
+                .iter()
+                .map(|(term, details)| format!("{term}\n\n: {details}"))
+                .join("\n\n"),
+            // references are plain superscripts by now, so keep the notes as text
+            SlideItem::Footnotes(notes) => footnote_lines(notes, usize::MAX).join("  \n"),
+            SlideItem::Table { headers, rows } => {
+                let row = |cells: &[String]| {
+                    let cells = cells
+                        .iter()
+                        .map(String::as_str)
+                        .chain(std::iter::repeat(""))
+                        .take(headers.len());
+                    format!("| {} |", cells.format(" | "))
+                };
+                std::iter::once(row(headers))
+                    .chain(std::iter::once(format!(
+                        "|{}",
+                        "---|".repeat(headers.len())
+                    )))
+                    .chain(rows.iter().map(|r| row(r)))
+                    .join("\n")
+            }
+        }
+    }
+
+    /// columns the item takes when drawn into `rect`, all of them if it fills or centers itself
+    fn width(&self, rect: Rect, opts: &RenderOptions) -> u16 {
+        let widest = |lines: &[Vec<(String, usize)>]| {
+            lines
+                .iter()
+                .map(|line| line.iter().map(|(piece, _)| piece.width()).sum::<usize>())
+                .max()
+                .unwrap_or(0)
+        };
+        let width = match self {
+            // the top level is centered across the slide
+            SlideItem::Heading(1, _) => rect.width as usize,
+            SlideItem::Heading(2, runs) => plain(runs).width() + 4,
+            SlideItem::Heading(_, runs) => plain(runs).width(),
+            SlideItem::Paragraph(runs) => widest(&wrap_runs(runs, rect.width as usize)),
+            SlideItem::Bullets(ls) => ls
+                .iter()
+                .map(|b| {
+                    let (marker, lines) = b.wrapped(rect.width as usize);
+                    marker.width() + widest(&lines)
+                })
+                .max()
+                .unwrap_or(0),
+            // border, padding and the indent around the text
+            SlideItem::Quote(src) => {
+                let lines = wrap(src, quote_width(rect));
+                lines.iter().map(|line| line.width()).max().unwrap_or(0) + 5
+            }
+            SlideItem::DefList(entries) => deflist_lines(entries, rect.width as usize)
+                .iter()
+                .map(|(line, _)| line.width())
+                .max()
+                .unwrap_or(0),
+            SlideItem::Footnotes(notes) => footnote_lines(notes, rect.width as usize)
+                .iter()
+                .map(|line| line.width())
+                .max()
+                .unwrap_or(0),
+            SlideItem::Code(code) => {
+                let gutter = gutter_width(&code.src, code.line_numbers || opts.code_line_numbers);
+                let widest = code
+                    .shown()
+                    .lines()
+                    .map(|line| line.width())
+                    .max()
+                    .unwrap_or(0);
+                let badge = match code.lang.width() {
+                    0 => 0,
+                    width => width + 1,
+                };
+                gutter as usize + widest + 2 + badge
+            }
+            SlideItem::Table { headers, rows } => {
+                let mut widths = headers.iter().map(|h| h.width()).collect::<Vec<_>>();
+                for row in rows {
+                    for (w, cell) in widths.iter_mut().zip(row) {
+                        *w = (*w).max(cell.width());
+                    }
+                }
+                widths.iter().sum::<usize>() + widths.len().saturating_sub(1)
+            }
+            SlideItem::Image { path, alt } => match image_draw(path, opts.image_mode) {
+                ImageDraw::Text => image_text(path, alt).width(),
+                _ => image_area(path, rect).width as usize,
+            },
+            SlideItem::Notes(_) => 0,
+            SlideItem::QR(_) | SlideItem::Math(_) | SlideItem::Rule => rect.width as usize,
+        };
+        (width as u16).min(rect.width)
+    }
+
+    /// rows taken by the item including the gap below it, when drawn into `rect`
+    fn height(&self, rect: Rect, opts: &RenderOptions) -> u16 {
+        match self {
+            SlideItem::Heading(1, _) => 4,
+            SlideItem::Heading(..) => 2,
+            SlideItem::Paragraph(runs) => wrap_runs(runs, rect.width as usize).len() as u16 + 2,
+            SlideItem::Bullets(ls) => {
+                let rows = ls.iter().map(|b| b.wrapped(rect.width as usize).1.len());
+                rows.sum::<usize>() as u16 + 2
+            }
+            SlideItem::Quote(src) => wrap(src, quote_width(rect)).len() as u16 + 2,
+            SlideItem::DefList(entries) => {
+                deflist_lines(entries, rect.width as usize).len() as u16 + 2
+            }
+            SlideItem::Footnotes(notes) => {
+                footnote_lines(notes, rect.width as usize).len() as u16 + 2
+            }
+            SlideItem::Code(code) if opts.code_wrap => {
+                let gutter = gutter_width(&code.src, code.line_numbers || opts.code_line_numbers);
+                let width = code_width(Rect {
+                    width: rect.width.saturating_sub(gutter),
+                    ..rect
+                })
+                .max(1);
+                let rows = code
+                    .shown()
+                    .lines()
+                    .map(|line| line.width().div_ceil(width).max(1))
+                    .sum::<usize>()
+                    .max(1);
+                rows as u16 + 2
+            }
+            SlideItem::Code(code) => code.src.lines().count().max(1) as u16 + 2,
+            SlideItem::QR(qr) => match qr_lines(qr, opts.qr_narrow) {
+                Ok(lines) if lines[0].width() <= rect.width as usize => {
+                    let caption = qr.caption.as_deref().unwrap_or_default();
+                    (lines.len() + wrap(caption, rect.width as usize).len()) as u16 + 2
+                }
+                // height of the error box
+                _ => 3 + 2,
+            },
+            SlideItem::Notes(_) => 0,
+            SlideItem::Math(src) => match math::unicode(src) {
+                Some(text) => text.lines().count() as u16 + 2,
+                None => src.lines().count() as u16 + 2,
+            },
+            SlideItem::Rule => 1 + 2,
+            SlideItem::Table { rows, .. } => rows.len() as u16 + 2 + 2,
+            SlideItem::Image { path, .. } => match image_draw(path, opts.image_mode) {
+                ImageDraw::Text => 2,
+                _ => image_area(path, rect).height + 2,
+            },
+        }
+    }
+
+    fn render<B: ratatui::backend::Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        rect: Rect,
+        opts: &RenderOptions,
+    ) -> u16 {
+        let theme = &opts.theme;
         match self {
-            SlideItem::Heading(src) => {
+            SlideItem::Heading(1, runs) => {
+                let x = Style::default()
+                    .bg(theme.heading_bg)
+                    .fg(theme.heading_fg)
+                    .italic()
+                    .bold();
+                let width = (plain(runs).width() as u16 + 8).min(rect.width);
+                let left = rect.x + (rect.width - width) / 2;
+                let line = runs
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, run)| (run.text.clone(), idx))
+                    .collect::<Vec<_>>();
+                let visible = clip(rect, frame.size());
+                let line = styled_line(runs, &line, left + 4, rect.y + 1, visible, x, theme);
+                render_clipped(
+                    frame,
+                    rect,
+                    ratatui::widgets::Paragraph::new(line)
+                        .block(Block::new().style(x).padding(widgets::Padding::uniform(1)))
+                        .alignment(Alignment::Center),
+                    Rect {
+                        x: left,
+                        width,
+                        height: 3,
+                        ..rect
+                    },
+                );
+                rect.y + self.height(rect, opts)
+            }
+            SlideItem::Heading(2, runs) => {
                 let x = Style::default()
-                    .bg(Color::Black)
-                    .fg(Color::White)
+                    .bg(theme.heading_bg)
+                    .fg(theme.heading2_fg)
                     .italic()
                     .bold();
                 let b = Block::default().style(x).title_alignment(Alignment::Center);
-                frame.render_widget(
-                    ratatui::widgets::Paragraph::new(src.as_str())
+                let line = runs
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, run)| (run.text.clone(), idx))
+                    .collect::<Vec<_>>();
+                let visible = clip(rect, frame.size());
+                let line = styled_line(runs, &line, rect.x + 2, rect.y, visible, x, theme);
+                render_clipped(
+                    frame,
+                    rect,
+                    ratatui::widgets::Paragraph::new(line)
                         .block(b)
                         .alignment(Alignment::Center),
                     Rect {
-                        width: src.len() as u16 + 4,
+                        width: plain(runs).width() as u16 + 4,
+                        height: 1,
+                        ..rect
+                    },
+                );
+                rect.y + self.height(rect, opts)
+            }
+            SlideItem::Heading(level, runs) => {
+                // the lower levels lose their emphasis step by step
+                let x = Style::default().fg(theme.heading3_fg);
+                let x = if *level == 3 { x.bold() } else { x.italic() };
+                let line = runs
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, run)| (run.text.clone(), idx))
+                    .collect::<Vec<_>>();
+                let visible = clip(rect, frame.size());
+                let line = styled_line(runs, &line, rect.x, rect.y, visible, x, theme);
+                render_clipped(
+                    frame,
+                    rect,
+                    ratatui::widgets::Paragraph::new(line),
+                    Rect {
+                        width: plain(runs).width() as u16,
                         height: 1,
                         ..rect
                     },
                 );
-                2 + rect.y
+                rect.y + self.height(rect, opts)
             }
-            SlideItem::Paragraph(src) => {
+            SlideItem::Paragraph(runs) => {
                 let x = Style::default().italic();
                 let b = Block::default().style(x).title_alignment(Alignment::Left);
-                let lines = src.lines().map(|x| x.len());
-                let max_len = lines.clone().max().unwrap_or(0);
-                let lines = lines.count();
-                let widget = ratatui::widgets::Paragraph::new(src.as_str())
-                    .wrap(Wrap { trim: true })
-                    .block(b);
-                let max_width = 80;
-                let height = (max_len / max_width) + 2;
-                let width = if height == 1 { max_len } else { max_width };
-                frame.render_widget(
-                    widget,
+                let lines = wrap_runs(runs, rect.width as usize);
+                let visible = clip(rect, frame.size());
+                let width = lines
+                    .iter()
+                    .map(|line| line.iter().map(|(piece, _)| piece.width()).sum::<usize>())
+                    .max()
+                    .unwrap_or(0) as u16;
+                let text = (rect.y..)
+                    .zip(&lines)
+                    .map(|(y, line)| styled_line(runs, line, rect.x, y, visible, x, theme))
+                    .collect::<Vec<_>>();
+                render_clipped(
+                    frame,
+                    rect,
+                    ratatui::widgets::Paragraph::new(text).block(b),
                     Rect {
-                        width: width as u16,
-                        height: height as u16,
+                        width,
+                        height: (lines.len() as u16).min(rect.height),
                         ..rect
                     },
                 );
-                lines as u16 + 2 + rect.y
+                rect.y + self.height(rect, opts)
             }
             SlideItem::Bullets(ls) => {
-                let lines = ls.iter().map(|x| x.len());
-                let max_len = lines.clone().max().unwrap_or(0);
-                let lines = lines.count();
+                let bullet_style = Style::default().fg(theme.bullet_fg);
+                let visible = clip(rect, frame.size());
+                let mut y = rect.y;
                 let items = ls
-                    .into_iter()
-                    .map(|x| ListItem::new("- ".to_string() + x.as_str()))
+                    .iter()
+                    .map(|b| {
+                        let (marker, lines) = b.wrapped(rect.width as usize);
+                        if b.hidden {
+                            y += lines.len() as u16;
+                            return ListItem::new(vec![Line::default(); lines.len()]);
+                        }
+                        let marker_width = marker.width();
+                        let mut marker = Some(marker);
+                        let base = match b.checked {
+                            Some(true) => Style::default().dim().crossed_out(),
+                            _ => Style::default(),
+                        };
+                        let lines = lines
+                            .iter()
+                            .map(|line| {
+                                // continuation lines line up under the text
+                                let prefix = match marker.take() {
+                                    Some(marker) => Span::styled(marker, bullet_style),
+                                    None => Span::raw(" ".repeat(marker_width)),
+                                };
+                                let x = rect.x + marker_width as u16;
+                                let mut line =
+                                    styled_line(&b.text, line, x, y, visible, base, theme);
+                                line.spans.insert(0, prefix);
+                                y += 1;
+                                line
+                            })
+                            .collect::<Vec<_>>();
+                        ListItem::new(lines)
+                    })
                     .collect::<Vec<_>>();
-                frame.render_widget(
+                render_clipped(
+                    frame,
+                    rect,
                     widgets::List::new(items),
                     Rect {
-                        width: max_len as u16 + 5,
-                        height: lines as u16,
+                        height: (self.height(rect, opts) - 2).min(rect.height),
                         ..rect
                     },
                 );
-                lines as u16 + 2 + rect.y
+                rect.y + self.height(rect, opts)
+            }
+            SlideItem::Quote(src) => {
+                let lines = wrap(src, quote_width(rect));
+                render_clipped(
+                    frame,
+                    rect,
+                    widgets::Paragraph::new(lines.join("\n"))
+                        .italic()
+                        .dim()
+                        .block(
+                            Block::new()
+                                .borders(Borders::LEFT)
+                                .padding(widgets::Padding::horizontal(1)),
+                        ),
+                    Rect {
+                        x: rect.x + 2,
+                        width: rect.width.saturating_sub(2),
+                        height: (lines.len() as u16).min(rect.height),
+                        ..rect
+                    },
+                );
+                rect.y + self.height(rect, opts)
+            }
+            SlideItem::DefList(entries) => {
+                let lines = deflist_lines(entries, rect.width as usize)
+                    .into_iter()
+                    .map(|(line, term)| {
+                        if term {
+                            Line::from(Span::raw(line).bold())
+                        } else {
+                            Line::from(line)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let height = (lines.len() as u16).min(rect.height);
+                render_clipped(
+                    frame,
+                    rect,
+                    widgets::Paragraph::new(lines),
+                    Rect { height, ..rect },
+                );
+                rect.y + self.height(rect, opts)
+            }
+            SlideItem::Footnotes(notes) => {
+                let lines = footnote_lines(notes, rect.width as usize)
+                    .into_iter()
+                    .map(Line::from)
+                    .collect::<Vec<_>>();
+                let height = (lines.len() as u16).min(rect.height);
+                render_clipped(
+                    frame,
+                    rect,
+                    widgets::Paragraph::new(lines).style(Style::default().dim()),
+                    Rect { height, ..rect },
+                );
+                rect.y + self.height(rect, opts)
             }
-            SlideItem::Code(src) => {
-                let text = ratatui::text::Text::raw(src.as_str());
-                let width = text.width();
+            SlideItem::Code(code) => {
+                let highlighted = if code.is_ansi() {
+                    Some(ansi::text(&code.src))
+                } else {
+                    highlight::highlight(&code.lang, &code.src, opts.code_background)
+                };
+                let mut text = highlighted.unwrap_or_else(|| {
+                    Text::styled(code.src.as_str(), Style::default().fg(theme.code_fg))
+                });
+                // even an empty block keeps a row for its border, so it
+                // never looks like inline code or nothing at all
+                if text.lines.is_empty() {
+                    text.lines.push(Line::default());
+                }
+                let gutter = gutter_width(&code.src, code.line_numbers || opts.code_line_numbers);
+                let full = rect;
+                let rect = Rect {
+                    x: rect.x + gutter.min(rect.width),
+                    width: rect.width.saturating_sub(gutter),
+                    ..rect
+                };
+                let max_width = code_width(rect);
+                let highlight = Style::default().bg(theme.code_highlight_bg);
+                for &idx in &code.highlight {
+                    let Some(line) = text.lines.get_mut(idx) else {
+                        continue;
+                    };
+                    // fill the rest of the row so the whole line stands out
+                    let fill = match line.width() % max_width.max(1) {
+                        0 if line.width() > 0 => 0,
+                        used => max_width - used,
+                    };
+                    line.spans.push(Span::raw(" ".repeat(fill)));
+                    for span in &mut line.spans {
+                        span.patch_style(highlight);
+                    }
+                }
+                // source line number of every row, `None` on wrapped continuations
+                let mut numbers = vec![];
+                let text = if opts.code_wrap {
+                    Text::from(
+                        text.lines
+                            .into_iter()
+                            .enumerate()
+                            .flat_map(|(idx, line)| {
+                                let rows = split_line(line, max_width);
+                                numbers.push(Some(idx + 1));
+                                numbers.extend(std::iter::repeat_n(None, rows.len() - 1));
+                                rows
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                } else {
+                    numbers.extend((1..=text.lines.len()).map(Some));
+                    text
+                };
+                if gutter > 0 {
+                    let digits = gutter as usize - 1;
+                    let gutter_text = numbers
+                        .iter()
+                        .map(|n| match n {
+                            Some(n) => format!("{n:>digits$}"),
+                            None => String::new(),
+                        })
+                        .join("\n");
+                    render_clipped(
+                        frame,
+                        full,
+                        ratatui::widgets::Paragraph::new(gutter_text).dim(),
+                        Rect {
+                            width: gutter,
+                            height: numbers.len() as u16,
+                            ..full
+                        },
+                    );
+                }
+                let width = text.width().min(max_width);
                 let height = text.height();
-                frame.render_widget(
-                    ratatui::widgets::Paragraph::new(text)
-                        .block(Block::new().borders(Borders::LEFT)),
+                let clipped = text
+                    .lines
+                    .iter()
+                    .map(|line| line.width() > max_width)
+                    .collect::<Vec<_>>();
+                render_clipped(
+                    frame,
+                    rect,
+                    ratatui::widgets::Paragraph::new(text).block(
+                        Block::new()
+                            .borders(Borders::LEFT)
+                            .border_style(Style::default().fg(theme.code_fg)),
+                    ),
                     Rect {
                         width: width as u16 + 2,
                         height: height as u16,
                         ..rect
                     },
                 );
-                height as u16 + 2 + rect.y
-            }
-            SlideItem::QR(src) => {
-                let qr = qrcode::QrCode::new(src)
-                    .unwrap()
-                    .render()
-                    .quiet_zone(false)
-                    .dark_color('█')
-                    .light_color(' ')
-                    .build();
-                let qr = qr
-                    .lines()
-                    .map(|x| x.chars().map(|c| [c, c]).flatten().collect::<String>())
-                    .join("\n");
-                let lines = qr.lines().map(|x| x.chars().count());
-                let max_len = lines.clone().max().unwrap_or(0);
-                let lines = lines.count();
-                frame.render_widget(
-                    ratatui::widgets::Paragraph::new(qr.as_str()),
+                // mark lines running past the right edge
+                for (y, _) in (rect.y..).zip(clipped).filter(|(_, clipped)| *clipped) {
+                    render_clipped(
+                        frame,
+                        rect,
+                        ratatui::widgets::Paragraph::new("›").dim(),
+                        Rect::new(rect.x + width as u16 + 1, y, 1, 1),
+                    );
+                }
+                // the language goes past the widest line, or over it when it fills the rect,
+                // leaving the `›` of a cut off first line in sight
+                let badge = code.lang.width() as u16;
+                if badge > 0 {
+                    render_clipped(
+                        frame,
+                        rect,
+                        ratatui::widgets::Paragraph::new(code.lang.as_str())
+                            .fg(theme.code_fg)
+                            .dim()
+                            .italic(),
+                        Rect {
+                            x: (rect.x + width as u16 + 3)
+                                .min(rect.right().saturating_sub(badge + 1)),
+                            width: badge,
+                            height: 1,
+                            ..rect
+                        },
+                    );
+                }
+                full.y + self.height(full, opts)
+            }
+            SlideItem::QR(qr) => {
+                let (lines, msg) = match qr_lines(qr, opts.qr_narrow) {
+                    Ok(lines) => (lines, None),
+                    Err(e) => (vec![], Some(format!("QR encoding failed: {e}"))),
+                };
+                let width = lines.first().map_or(0, |line| line.width()) as u16;
+                let msg = msg.or_else(|| {
+                    // a partly drawn code can't be scanned, say so instead
+                    (width > rect.width || lines.len() > rect.height as usize)
+                        .then(|| "terminal too small for the QR code".to_owned())
+                });
+                if let Some(msg) = msg {
+                    render_clipped(
+                        frame,
+                        rect,
+                        widgets::Paragraph::new(msg.as_str())
+                            .red()
+                            .block(Block::new().borders(Borders::ALL).red()),
+                        Rect {
+                            width: (msg.width() as u16 + 2).min(rect.width),
+                            height: 3.min(rect.height),
+                            ..rect
+                        },
+                    );
+                    return rect.y + self.height(rect, opts);
+                }
+                render_clipped(
+                    frame,
+                    rect,
+                    ratatui::widgets::Paragraph::new(lines.join("\n")),
+                    Rect {
+                        x: rect.x + (rect.width - width) / 2,
+                        width,
+                        height: lines.len() as u16,
+                        ..rect
+                    },
+                );
+                if let Some(caption) = &qr.caption {
+                    let caption = wrap(caption, rect.width as usize);
+                    render_clipped(
+                        frame,
+                        rect,
+                        widgets::Paragraph::new(caption.join("\n"))
+                            .alignment(Alignment::Center)
+                            .dim(),
+                        Rect {
+                            y: rect.y + lines.len() as u16,
+                            height: caption.len() as u16,
+                            ..rect
+                        },
+                    );
+                }
+                rect.y + self.height(rect, opts)
+            }
+            SlideItem::Notes(_) => rect.y + self.height(rect, opts),
+            SlideItem::Math(src) => {
+                let widget = match math::unicode(src) {
+                    Some(text) => widgets::Paragraph::new(text).alignment(Alignment::Center),
+                    // too much latex to approximate, show the source like code
+                    None => widgets::Paragraph::new(src.trim_end()).block(
+                        Block::new()
+                            .borders(Borders::LEFT)
+                            .border_style(Style::default().fg(theme.code_fg)),
+                    ),
+                };
+                render_clipped(
+                    frame,
+                    rect,
+                    widget,
+                    Rect {
+                        height: self.height(rect, opts) - 2,
+                        ..rect
+                    },
+                );
+                rect.y + self.height(rect, opts)
+            }
+            SlideItem::Rule => {
+                render_clipped(
+                    frame,
+                    rect,
+                    ratatui::widgets::Paragraph::new("─".repeat(rect.width as usize)).dim(),
+                    Rect { height: 1, ..rect },
+                );
+                rect.y + self.height(rect, opts)
+            }
+            SlideItem::Table { headers, rows } => {
+                let cols = headers.len();
+                let mut widths = headers.iter().map(|h| h.width()).collect::<Vec<_>>();
+                for row in rows {
+                    for (w, cell) in widths.iter_mut().zip(row) {
+                        *w = (*w).max(cell.width());
+                    }
+                }
+                let header = widgets::Row::new(headers.iter().map(String::as_str))
+                    .style(Style::default().bold())
+                    .bottom_margin(1);
+                let body = rows.iter().map(|row| {
+                    // pad ragged rows so every row matches the header
+                    widgets::Row::new(
+                        row.iter()
+                            .map(String::as_str)
+                            .chain(std::iter::repeat(""))
+                            .take(cols),
+                    )
+                });
+                let constraints = widths
+                    .iter()
+                    .map(|&w| Constraint::Length(w as u16))
+                    .collect::<Vec<_>>();
+                let width = widths.iter().sum::<usize>() + cols.saturating_sub(1);
+                let height = rows.len() as u16 + 2;
+                render_clipped(
+                    frame,
+                    rect,
+                    widgets::Table::new(body)
+                        .header(header)
+                        .widths(&constraints),
                     Rect {
-                        width: max_len as u16,
-                        height: lines as u16,
+                        width: (width as u16).min(rect.width),
+                        height,
                         ..rect
                     },
                 );
-                lines as u16 + 2 + rect.y
+                rect.y + self.height(rect, opts)
             }
+            SlideItem::Image { path, alt } => match image_draw(path, opts.image_mode) {
+                ImageDraw::Protocol => {
+                    let area = image_area(path, rect);
+                    render_clipped(frame, rect, widgets::Clear, area);
+                    graphics::queue(path, area);
+                    rect.y + self.height(rect, opts)
+                }
+                ImageDraw::Art(picture, art) => {
+                    let area = image_area(path, rect);
+                    let lines = art::lines(&picture, area, art);
+                    render_clipped(frame, rect, widgets::Paragraph::new(lines), area);
+                    rect.y + self.height(rect, opts)
+                }
+                ImageDraw::Text => {
+                    let src = image_text(path, alt);
+                    render_clipped(
+                        frame,
+                        rect,
+                        widgets::Paragraph::new(src.as_str()).italic().dim(),
+                        Rect {
+                            width: (src.width() as u16).min(rect.width),
+                            height: 1,
+                            ..rect
+                        },
+                    );
+                    rect.y + self.height(rect, opts)
+                }
+            },
         }
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct Slide {
-    title: String,
-    items: Vec<SlideItem>,
+/// rows of the QR code for `qr`, every module is two columns wide unless `narrow`
+/// so the code comes out roughly square
+fn qr_lines(qr: &QrBlock, narrow: bool) -> Result<Vec<String>, qrcode::types::QrError> {
+    let qr = qr
+        .encode()?
+        .render()
+        .quiet_zone(false)
+        .dark_color('█')
+        .light_color(' ')
+        .build();
+    let width = if narrow { 1 } else { 2 };
+    Ok(qr
+        .lines()
+        .map(|line| {
+            line.chars()
+                .flat_map(|c| std::iter::repeat_n(c, width))
+                .collect()
+        })
+        .collect())
 }
 
-#[derive(Debug)]
-pub(crate) struct Slides {
-    title: String,
-    slides: Vec<Slide>,
-    current_idx: usize,
+/// renders `widget` into the part of `area` that lies within `bounds`,
+/// so items running past the bottom of the screen are cut off instead of panicking
+fn render_clipped<B: ratatui::backend::Backend, W: widgets::Widget>(
+    frame: &mut Frame<B>,
+    bounds: Rect,
+    widget: W,
+    area: Rect,
+) {
+    let bounds = clip(bounds, frame.size());
+    let area = clip(area, bounds);
+    if area.area() > 0 {
+        frame.render_widget(widget, area);
+    }
 }
 
-impl Slides {
-    pub(crate) fn current(&self) -> Option<&Slide> {
-        self.slides.get(self.current_idx)
+/// overlap of two rects, empty when they don't touch
+fn clip(a: Rect, b: Rect) -> Rect {
+    if a.intersects(b) {
+        a.intersection(b)
+    } else {
+        Rect::default()
     }
-    pub(crate) fn next(&mut self) {
-        self.current_idx = (self.current_idx + 1).min(self.slides.len() - 1);
+}
+
+/// text columns left for code beside its bar, keeping one for the clip marker
+fn code_width(rect: Rect) -> usize {
+    rect.width.saturating_sub(2) as usize
+}
+
+/// columns taken by right aligned line numbers and the space after them
+fn gutter_width(src: &str, numbered: bool) -> u16 {
+    if numbered {
+        src.lines().count().max(1).to_string().len() as u16 + 1
+    } else {
+        0
     }
-    pub(crate) fn prev(&mut self) {
-        self.current_idx = self.current_idx.saturating_sub(1)
+}
+
+/// breaks a line of code into rows of at most `width` columns
+fn split_line(line: Line<'_>, width: usize) -> Vec<Line<'_>> {
+    let mut rows = vec![Line::default()];
+    let mut used = 0;
+    for span in line.spans {
+        let mut chunk = String::new();
+        for c in span.content.chars() {
+            let w = c.width().unwrap_or(0);
+            if used + w > width && used > 0 {
+                let row = rows.last_mut().unwrap();
+                row.spans
+                    .push(Span::styled(std::mem::take(&mut chunk), span.style));
+                rows.push(Line::default());
+                used = 0;
+            }
+            chunk.push(c);
+            used += w;
+        }
+        if !chunk.is_empty() {
+            rows.last_mut()
+                .unwrap()
+                .spans
+                .push(Span::styled(chunk, span.style));
+        }
     }
+    rows
 }
 
-pub(crate) fn mkslides(path: impl AsRef<str>) -> Result<Slides> {
-    let md_slides = std::fs::read_to_string(path.as_ref())?;
-    use comrak::{parse_document, Arena};
-    let arena = Arena::new();
-    let slides = md_slides
-        .split("---")
-        .map(|x| x.trim_matches('-').trim())
-        .map(|x| parse_document(&arena, x, &comrak::ComrakOptions::default()))
-        .map(|node| {
-            let mut items = vec![];
-            let mut new = true;
-            node.traverse().for_each(|node| {
-                let node = match node {
-                    NodeEdge::Start(node) => node,
-                    NodeEdge::End(_) => {
-                        new = true;
-                        return;
-                    }
-                };
-                match &node.data.borrow().value {
-                    NodeValue::List(_) => {
-                        // println!("## LIST");
-                        items.push(SlideItem::Bullets(vec![]));
-                        new = false;
-                    }
-                    NodeValue::Heading(_) => {
-                        items.push(SlideItem::Heading("".into()));
-                        new = false;
-                    }
-                    NodeValue::CodeBlock(codeblock) => {
-                        match codeblock.info.as_str() {
-                            "qrcode" => {
-                                items.push(SlideItem::QR(codeblock.literal.trim().to_owned()));
-                            }
-                            _ => {
-                                items.push(SlideItem::Code(codeblock.literal.clone()));
-                            }
-                        }
-                        new = false;
-                    }
-                    NodeValue::Item(_) => {
-                        // println!("## ITEM");
-                        items.last_mut().map(|item| {
-                            if let SlideItem::Bullets(bullets) = item {
-                                bullets.push("".into());
-                            }
-                        });
-                        new = false;
-                    }
-                    NodeValue::Code(code) => {
-                        let src = code.literal.as_str();
-                        if new {
-                            items.push(SlideItem::Paragraph("".into()));
-                            new = false;
-                        }
-                        items.last_mut().map(|item| match item {
-                            SlideItem::Paragraph(psrc) | SlideItem::Heading(psrc) => {
-                                psrc.push('`');
-                                psrc.push_str(src);
-                                psrc.push('`');
-                            }
-                            SlideItem::Bullets(bullets) => {
-                                bullets.last_mut().map(|b| {
-                                    b.push('`');
-                                    b.push_str(src);
-                                    b.push('`');
-                                });
-                            }
-                            _ => {}
-                        });
-                    }
-                    NodeValue::Text(src) => {
-                        // println!("{src}");
-                        if new {
-                            items.push(SlideItem::Paragraph("".into()));
-                            new = false;
-                        }
-                        items.last_mut().map(|item| match item {
-                            SlideItem::Paragraph(psrc) | SlideItem::Heading(psrc) => {
-                                psrc.push_str(src)
-                            }
-                            SlideItem::Bullets(bullets) => {
-                                bullets.last_mut().map(|b| b.push_str(src));
-                            }
+/// wrapped rows of a definition list, `true` for the rows of a term,
+/// descriptions are indented below their term
+fn deflist_lines(entries: &[(String, String)], width: usize) -> Vec<(String, bool)> {
+    const INDENT: usize = 4;
+    entries
+        .iter()
+        .flat_map(|(term, details)| {
+            let term = wrap(term, width).into_iter().map(|line| (line, true));
+            let details = wrap(details, width.saturating_sub(INDENT))
+                .into_iter()
+                .map(|line| (format!("{}{line}", " ".repeat(INDENT)), false));
+            term.chain(details)
+        })
+        .collect()
+}
+
+/// each note wrapped after its superscript number
+fn footnote_lines(notes: &[String], width: usize) -> Vec<String> {
+    notes
+        .iter()
+        .enumerate()
+        .flat_map(|(i, note)| {
+            let marker = footnote_marker(&(i + 1).to_string());
+            let indent = marker.width() + 1;
+            wrap(note, width.saturating_sub(indent))
+                .into_iter()
+                .enumerate()
+                .map(move |(row, line)| match row {
+                    0 => format!("{marker} {line}"),
+                    _ => format!("{}{line}", " ".repeat(indent)),
+                })
+        })
+        .collect()
+}
+
+/// the number of a footnote as drawn next to the text referencing it
+fn footnote_marker(number: &str) -> String {
+    math::superscript(number).unwrap_or_else(|| format!("[{number}]"))
+}
+
+/// text columns left inside a quote after its indent, bar and padding
+fn quote_width(rect: Rect) -> usize {
+    rect.width.saturating_sub(5) as usize
+}
+
+/// where the image `src` of a markdown file in `base_dir` is, urls are kept as they are
+pub(crate) fn image_path(base_dir: &Path, src: &str) -> PathBuf {
+    if is_url(Path::new(src)) {
+        src.into()
+    } else {
+        base_dir.join(src)
+    }
+}
+
+/// whether an image is on the web instead of on disk, like `https://...`
+pub(crate) fn is_url(path: &Path) -> bool {
+    path.to_str()
+        .and_then(|src| src.split_once("://"))
+        .is_some_and(|(scheme, _)| {
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        })
+}
+
+/// what's drawn for an image that can't be shown, its alt text or else its url
+fn image_text(path: &Path, alt: &str) -> String {
+    if alt.is_empty() && is_url(path) {
+        format!("[image: {}]", path.display())
+    } else {
+        format!("[image: {alt}]")
+    }
+}
+
+/// how an image ends up on screen
+enum ImageDraw {
+    Protocol,
+    Art(Rc<Picture>, Art),
+    Text,
+}
+
+fn image_draw(path: &Path, mode: ImageMode) -> ImageDraw {
+    if is_url(path) {
+        // nothing is downloaded
+        return ImageDraw::Text;
+    }
+    let art = |art| match art::load(path) {
+        Some(picture) => ImageDraw::Art(picture, art),
+        None => ImageDraw::Text,
+    };
+    match mode {
+        ImageMode::Auto | ImageMode::Protocol if graphics::supported(path) => ImageDraw::Protocol,
+        ImageMode::Auto | ImageMode::Blocks => art(Art::Blocks),
+        ImageMode::Braille => art(Art::Braille),
+        ImageMode::Protocol | ImageMode::Text => ImageDraw::Text,
+    }
+}
+
+fn image_area(path: &Path, rect: Rect) -> Rect {
+    graphics::fit(
+        path,
+        Rect {
+            height: rect.height.saturating_sub(1),
+            ..rect
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct Slide {
+    title: String,
+    /// set with `<!-- id: name -->` to jump to the slide by name
+    id: Option<String>,
+    /// set with `<!-- align: center -->`, items are left aligned otherwise
+    align: Option<Alignment>,
+    /// index of the first item in the right column, for slides split with `---col---`
+    column_break: Option<usize>,
+    /// set with `<!-- bg: blue -->` to fill the whole slide
+    bg: Option<Color>,
+    /// set with `<!-- run: ./setup.sh -->`, a shell command started when the slide is shown
+    run: Option<String>,
+    items: Vec<SlideItem>,
+    /// 0 for a slide as written, counts up for the pages it was split into
+    part: usize,
+    /// the contents slide made by `--toc`, or one of its pages
+    toc: bool,
+}
+
+impl Slide {
+    /// a slide of `items` without any directives, titled once it's in a deck
+    pub(crate) fn new(items: Vec<SlideItem>) -> Self {
+        Self {
+            title: String::new(),
+            id: None,
+            align: None,
+            column_break: None,
+            bg: None,
+            run: None,
+            items,
+            part: 0,
+            toc: false,
+        }
+    }
+    pub fn items(&self) -> &[SlideItem] {
+        &self.items
+    }
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+    pub(crate) fn part(&self) -> usize {
+        self.part
+    }
+    pub(crate) fn align(&self) -> Option<Alignment> {
+        self.align
+    }
+    pub(crate) fn column_break(&self) -> Option<usize> {
+        self.column_break
+    }
+    pub(crate) fn bg(&self) -> Option<Color> {
+        self.bg
+    }
+    pub(crate) fn is_toc(&self) -> bool {
+        self.toc
+    }
+    /// a lone heading, optionally followed by one paragraph as the subtitle
+    pub(crate) fn is_title_slide(&self) -> bool {
+        let mut visible = self
+            .items
+            .iter()
+            .filter(|item| !matches!(item, SlideItem::Notes(_)));
+        matches!(
+            (visible.next(), visible.next(), visible.next()),
+            (
+                Some(SlideItem::Heading(..)),
+                None | Some(SlideItem::Paragraph(_)),
+                None
+            )
+        )
+    }
+
+    /// the first heading, or the first line of the first paragraph without one
+    pub fn name(&self) -> Option<String> {
+        self.heading().or_else(|| {
+            self.items.iter().find_map(|item| match item {
+                SlideItem::Paragraph(runs) => plain(runs).lines().next().map(Into::into),
+                _ => None,
+            })
+        })
+    }
+
+    /// text of the first heading
+    pub fn heading(&self) -> Option<String> {
+        self.items.iter().find_map(|item| match item {
+            SlideItem::Heading(_, runs) => Some(plain(runs)),
+            _ => None,
+        })
+    }
+
+    /// writes the slide back out as markdown that parses into the same items
+    pub fn to_markdown(&self) -> String {
+        let mut directives = vec![];
+        if let Some(id) = &self.id {
+            directives.push(format!("<!-- id: {id} -->"));
+        }
+        if let Some(align) = self.align {
+            let align = format!("{align:?}").to_lowercase();
+            directives.push(format!("<!-- align: {align} -->"));
+        }
+        if let Some(bg) = self.bg {
+            directives.push(format!("<!-- bg: {} -->", color_name(bg)));
+        }
+        if let Some(run) = &self.run {
+            directives.push(format!("<!-- run: {run} -->"));
+        }
+        if self.items.is_empty() && directives.is_empty() {
+            return BLANK_SLIDE.into();
+        }
+        let mut items = self
+            .items
+            .iter()
+            .map(SlideItem::to_markdown)
+            .collect::<Vec<_>>();
+        if let Some(idx) = self.column_break {
+            items.insert(idx, COLUMN_BREAK.into());
+        }
+        directives.into_iter().chain(items).join("\n\n")
+    }
+
+    /// the visible text of the slide, one entry per block of text
+    fn outline(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .flat_map(|item| match item {
+                SlideItem::Heading(_, runs) | SlideItem::Paragraph(runs) => vec![plain(runs)],
+                SlideItem::Bullets(bullets) => bullets.iter().map(|b| plain(&b.text)).collect(),
+                SlideItem::Quote(src) => vec![src.clone()],
+                SlideItem::DefList(entries) => entries
+                    .iter()
+                    .flat_map(|(term, details)| [term.clone(), details.clone()])
+                    .collect(),
+                SlideItem::Footnotes(notes) => notes.clone(),
+                SlideItem::Table { headers, rows } => headers
+                    .iter()
+                    .chain(rows.iter().flatten())
+                    .cloned()
+                    .collect(),
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    /// the visible text of the slide, lowercased for searching
+    fn search_text(&self) -> String {
+        self.outline().join("\n").to_lowercase()
+    }
+
+    /// all speaker notes of the slide joined together
+    pub fn notes(&self) -> String {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                SlideItem::Notes(notes) => Some(notes.trim_end()),
+                _ => None,
+            })
+            .join("\n")
+    }
+}
+
+/// deck metadata from a yaml block fenced by `---` at the top of the file
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub(crate) struct Frontmatter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) author: Option<String>,
+    /// `math: true` renders `$...$` in text like `--math` does
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) math: Option<bool>,
+    /// every other key, substituted for `{{key}}` in the slides
+    #[serde(flatten)]
+    pub(crate) vars: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl Frontmatter {
+    /// `true` if the deck had no frontmatter or an empty one
+    pub(crate) fn is_empty(&self) -> bool {
+        self.title.is_none() && self.author.is_none() && self.math.is_none() && self.vars.is_empty()
+    }
+
+    /// text of the variable `name`, the title and author can be used too
+    fn var(&self, name: &str) -> Option<String> {
+        match name {
+            "title" => self.title.clone(),
+            "author" => self.author.clone(),
+            _ => match self.vars.get(name)? {
+                serde_yaml::Value::String(s) => Some(s.clone()),
+                serde_yaml::Value::Number(n) => Some(n.to_string()),
+                serde_yaml::Value::Bool(b) => Some(b.to_string()),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// replaces `{{name}}` with the frontmatter variable `name`, `\{{` is kept as a
+/// plain `{{`, returns the text and the names of variables that aren't defined
+pub(crate) fn expand_vars(src: &str, meta: &Frontmatter) -> (String, Vec<String>) {
+    let mut out = String::new();
+    let mut undefined = vec![];
+    let mut rest = src;
+    while let Some(start) = rest.find("{{") {
+        if rest[..start].ends_with('\\') {
+            out.push_str(&rest[..start - 1]);
+            out.push_str("{{");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str("{{");
+            rest = after;
+            continue;
+        };
+        let name = after[..end].trim();
+        match meta.var(name) {
+            Some(value) => out.push_str(&value),
+            None => {
+                undefined.push(name.to_owned());
+                out.push_str(&rest[start..start + end + 4]);
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    (out, undefined)
+}
+
+#[derive(Debug)]
+pub struct Slides {
+    title: String,
+    meta: Frontmatter,
+    slides: Vec<Slide>,
+    current_idx: usize,
+    /// bullets shown so far on the current slide, `None` unless revealing them one by one
+    revealed: Option<usize>,
+    /// step from the last slide to the first and back instead of stopping
+    wrap: bool,
+    /// whether the `--toc` contents slide takes a number in the slide counter
+    toc_counted: bool,
+    /// rows each slide that doesn't fit is scrolled down, by index
+    scroll: BTreeMap<usize, u16>,
+    /// directory of the markdown file, relative paths in the slides start here
+    base_dir: PathBuf,
+    /// files pulled in by `include` directives
+    includes: Vec<PathBuf>,
+}
+
+impl Slides {
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    pub(crate) fn meta(&self) -> &Frontmatter {
+        &self.meta
+    }
+    pub fn slides(&self) -> &[Slide] {
+        &self.slides
+    }
+    pub(crate) fn current_idx(&self) -> usize {
+        self.current_idx
+    }
+    /// shows the bullets of each slide one at a time on `next`
+    pub(crate) fn set_incremental(&mut self, incremental: bool) {
+        self.revealed = incremental.then_some(0);
+    }
+    pub(crate) fn is_incremental(&self) -> bool {
+        self.revealed.is_some()
+    }
+    /// makes `next` on the last slide go to the first and `prev` on the first to the last
+    pub(crate) fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+    pub(crate) fn is_wrap(&self) -> bool {
+        self.wrap
+    }
+    /// reveals the next hidden bullet, or moves on once all are shown
+    pub(crate) fn next(&mut self) {
+        let bullets = self.bullet_count(self.current_idx);
+        match &mut self.revealed {
+            Some(revealed) if *revealed < bullets => *revealed += 1,
+            _ if self.wrap && self.current_idx + 1 >= self.slides.len() => self.goto(0),
+            _ => self.goto(self.current_idx + 1),
+        }
+    }
+    /// hides the last revealed bullet, or goes back to the fully shown previous slide
+    pub(crate) fn prev(&mut self) {
+        match &mut self.revealed {
+            Some(revealed) if *revealed > 0 => *revealed -= 1,
+            _ if self.current_idx > 0 || self.wrap => {
+                self.goto(
+                    self.current_idx
+                        .checked_sub(1)
+                        .unwrap_or_else(|| self.slides.len().saturating_sub(1)),
+                );
+                let bullets = self.bullet_count(self.current_idx);
+                if let Some(revealed) = &mut self.revealed {
+                    *revealed = bullets;
+                }
+            }
+            _ => {}
+        }
+    }
+    /// `true` on the last slide once all of its bullets are shown
+    pub(crate) fn at_end(&self) -> bool {
+        let bullets = self.bullet_count(self.current_idx);
+        self.current_idx + 1 >= self.slides.len()
+            && self.revealed.is_none_or(|revealed| revealed >= bullets)
+    }
+    pub(crate) fn first(&mut self) {
+        self.goto(0);
+    }
+    pub(crate) fn last(&mut self) {
+        self.goto(self.slides.len().saturating_sub(1));
+    }
+    /// jumps to the slide with the id `name`, `false` if there is none
+    pub(crate) fn goto_name(&mut self, name: &str) -> bool {
+        let idx = self
+            .slides
+            .iter()
+            .position(|slide| slide.id.as_deref() == Some(name));
+        if let Some(idx) = idx {
+            self.goto(idx);
+        }
+        idx.is_some()
+    }
+
+    /// jumps to the next slide after the current one mentioning `query`, ignoring case
+    /// and wrapping around the deck, or the one before it if not `forward`
+    pub(crate) fn search(&mut self, query: &str, forward: bool) -> bool {
+        let query = query.to_lowercase();
+        let len = self.slides.len();
+        let found = (1..=len)
+            .map(|step| {
+                if forward {
+                    (self.current_idx + step) % len
+                } else {
+                    (self.current_idx + len * 2 - step) % len
+                }
+            })
+            .find(|&idx| self.slides[idx].search_text().contains(&query));
+        if let Some(idx) = found {
+            self.goto(idx);
+        }
+        found.is_some()
+    }
+
+    pub(crate) fn goto(&mut self, idx: usize) {
+        let idx = idx.min(self.slides.len().saturating_sub(1));
+        if idx != self.current_idx {
+            self.current_idx = idx;
+            if let Some(revealed) = &mut self.revealed {
+                *revealed = 0;
+            }
+        }
+    }
+
+    fn bullet_count(&self, idx: usize) -> usize {
+        self.slides.get(idx).map_or(0, |slide| {
+            slide
+                .items
+                .iter()
+                .map(|item| match item {
+                    SlideItem::Bullets(bullets) => bullets.len(),
+                    _ => 0,
+                })
+                .sum()
+        })
+    }
+
+    /// index of the current slide as written, not counting split off pages
+    pub(crate) fn source_idx(&self) -> usize {
+        self.slides[..(self.current_idx + 1).min(self.slides.len())]
+            .iter()
+            .filter(|slide| slide.part == 0)
+            .count()
+            .saturating_sub(1)
+    }
+
+    pub(crate) fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// the included files the deck was put together from, so they can be watched too
+    pub(crate) fn includes(&self) -> &[PathBuf] {
+        &self.includes
+    }
+
+    /// the `run` command of the current slide, set on the first of its pages
+    pub(crate) fn current_run(&self) -> Option<&str> {
+        let end = (self.current_idx + 1).min(self.slides.len());
+        let start = self.slides[..end]
+            .iter()
+            .rposition(|slide| slide.part == 0)?;
+        self.slides[start].run.as_deref()
+    }
+
+    /// speaker notes of every page the current slide was split into
+    pub(crate) fn current_notes(&self) -> String {
+        let end = (self.current_idx + 1).min(self.slides.len());
+        let start = self.slides[..end]
+            .iter()
+            .rposition(|slide| slide.part == 0)
+            .unwrap_or(0);
+        self.slides[start..]
+            .iter()
+            .enumerate()
+            .take_while(|(idx, slide)| *idx == 0 || slide.part > 0)
+            .map(|(_, slide)| slide.notes())
+            .filter(|notes| !notes.is_empty())
+            .join("\n")
+    }
+
+    /// source of the code block at `item` of the current slide, or of all its
+    /// code blocks one after the other if `item` isn't one
+    pub(crate) fn current_code(&self, item: Option<usize>) -> Option<String> {
+        let items = &self.slides.get(self.current_idx)?.items;
+        if let Some(SlideItem::Code(code)) = item.and_then(|item| items.get(item)) {
+            return Some(code.shown().into_owned()).filter(|code| !code.is_empty());
+        }
+        let code = items
+            .iter()
+            .filter_map(|item| match item {
+                SlideItem::Code(code) => Some(code.shown()),
+                _ => None,
+            })
+            .join("\n");
+        (!code.is_empty()).then_some(code)
+    }
+
+    /// indices of the items on the current slide focus mode can zoom into
+    pub(crate) fn zoomable(&self) -> Vec<usize> {
+        self.slides
+            .get(self.current_idx)
+            .map(|slide| slide.items.iter().positions(SlideItem::zoomable).collect())
+            .unwrap_or_default()
+    }
+
+    /// splits slides that don't fit in `size` into pages at item boundaries,
+    /// undoing any earlier split first so this can run again on resize
+    pub(crate) fn paginate(&mut self, size: Rect, opts: &RenderOptions) {
+        let current = self.source_idx();
+        let mut merged: Vec<Slide> = vec![];
+        for slide in std::mem::take(&mut self.slides) {
+            match merged.last_mut() {
+                Some(prev) if slide.part > 0 => prev.items.extend(slide.items),
+                _ => merged.push(slide),
+            }
+        }
+        for (idx, slide) in merged.into_iter().enumerate() {
+            if idx == current {
+                self.current_idx = self.slides.len();
+            }
+            // columns are laid out side by side, splitting them into pages would tear them apart
+            if slide.is_title_slide() || slide.column_break.is_some() {
+                self.slides.push(slide);
+                continue;
+            }
+            let top = content_top(size, opts);
+            let mut pages = vec![vec![]];
+            let mut y = top;
+            for item in slide.items {
+                let height = item.height(item_rect(size, y, opts.margin), opts);
+                // the gap below the last item may run past the bottom
+                if y + height > content_bottom(size) + 2 && y > top {
+                    pages.push(vec![]);
+                    y = top;
+                }
+                y += item.height(item_rect(size, y, opts.margin), opts);
+                pages.last_mut().unwrap().push(item);
+            }
+            for (part, items) in pages.into_iter().enumerate() {
+                self.slides.push(Slide {
+                    title: slide.title.clone(),
+                    // only the first page is jumped to
+                    id: slide.id.clone().filter(|_| part == 0),
+                    align: slide.align,
+                    column_break: None,
+                    bg: slide.bg,
+                    run: slide.run.clone().filter(|_| part == 0),
+                    items,
+                    part,
+                    toc: slide.toc,
+                });
+            }
+        }
+        self.number_toc();
+    }
+
+    /// puts a contents slide listing the heading of every slide after the title
+    /// slide, or first if the deck doesn't start with one
+    fn insert_toc(&mut self, counted: bool) {
+        let entries = self
+            .slides
+            .iter()
+            .filter_map(Slide::heading)
+            .map(|heading| Bullet {
+                depth: 0,
+                number: None,
+                checked: None,
+                text: vec![Run {
+                    text: heading,
+                    modifier: Modifier::empty(),
+                    link: None,
+                    code: false,
+                }],
+                hidden: false,
+            })
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            return;
+        }
+        let heading = Run {
+            text: "Contents".into(),
+            modifier: Modifier::empty(),
+            link: None,
+            code: false,
+        };
+        let toc = Slide {
+            title: heading.text.clone(),
+            toc: true,
+            ..Slide::new(vec![
+                SlideItem::Heading(1, vec![heading]),
+                SlideItem::Bullets(entries),
+            ])
+        };
+        let at = usize::from(self.slides[0].is_title_slide());
+        self.slides.insert(at, toc);
+        self.toc_counted = counted;
+        self.number_toc();
+    }
+
+    /// numbers the entries of the contents slide the way the slide counter shows
+    /// the slides they list, which changes when slides are split into pages
+    fn number_toc(&mut self) {
+        let numbers = (0..self.slides.len())
+            .filter(|&idx| {
+                let slide = &self.slides[idx];
+                slide.part == 0 && !slide.toc && slide.heading().is_some()
+            })
+            .map(|idx| self.numbering(idx).0 + 1)
+            .collect::<Vec<_>>();
+        let mut numbers = numbers.into_iter();
+        for slide in self.slides.iter_mut().filter(|slide| slide.toc) {
+            for item in &mut slide.items {
+                if let SlideItem::Bullets(bullets) = item {
+                    for bullet in bullets {
+                        bullet.number = numbers.next();
+                    }
+                }
+            }
+        }
+    }
+
+    /// whether slide `idx` takes a number in the slide counter
+    fn is_numbered(&self, idx: usize) -> bool {
+        self.toc_counted || self.slides.get(idx).is_none_or(|slide| !slide.toc)
+    }
+
+    /// position of slide `idx` among the numbered slides and how many there are,
+    /// an uncounted contents slide shares the position of the slide before it
+    fn numbering(&self, idx: usize) -> (usize, usize) {
+        let numbered = (0..self.slides.len())
+            .filter(|&idx| self.is_numbered(idx))
+            .collect::<Vec<_>>();
+        let before = numbered
+            .iter()
+            .take_while(|&&numbered| numbered < idx)
+            .count();
+        let position = if self.is_numbered(idx) {
+            before
+        } else {
+            before.saturating_sub(1)
+        };
+        (position, numbered.len())
+    }
+
+    /// the slide counter shown on slide `idx`
+    pub(crate) fn counter(&self, idx: usize) -> String {
+        if !self.is_numbered(idx) {
+            return "contents".into();
+        }
+        let (position, count) = self.numbering(idx);
+        slide_counter(position, count)
+    }
+
+    /// how far the progress bar is filled on slide `idx`
+    pub(crate) fn progress(&self, idx: usize) -> f64 {
+        let (position, count) = self.numbering(idx);
+        progress(position, count.max(1))
+    }
+
+    /// scrolls the current slide a row `down` or up when it doesn't fit in `area`,
+    /// `false` if it's already as far as it goes
+    pub(crate) fn scroll(&mut self, down: bool, area: Rect, opts: &RenderOptions) -> bool {
+        let idx = self.current_idx;
+        let Some(view) = SlideView::new(self, idx, self.revealed, opts.title_content) else {
+            return false;
+        };
+        let overflow = view.overflow(area, opts);
+        let rows = view.scroll.min(overflow);
+        let scrolled = if down {
+            (rows + 1).min(overflow)
+        } else {
+            rows.saturating_sub(1)
+        };
+        self.scroll.insert(idx, scrolled);
+        scrolled != rows
+    }
+
+    /// jumps to the slide the counter shows as number `n`, or the last one past the end
+    pub(crate) fn goto_number(&mut self, n: usize) {
+        let idx = (0..self.slides.len())
+            .find(|&idx| self.is_numbered(idx) && self.numbering(idx).0 + 1 >= n.max(1))
+            .unwrap_or(self.slides.len());
+        self.goto(idx);
+    }
+
+    /// rows slide `idx` runs past the bottom of `area`, measuring each of its columns
+    pub(crate) fn overflow(&self, idx: usize, area: Rect, opts: &RenderOptions) -> u16 {
+        SlideView::new(self, idx, None, opts.title_content)
+            .map_or(0, |view| view.overflow(area, opts))
+    }
+}
+
+fn options(opts: &ParseOptions) -> comrak::ComrakOptions {
+    let mut options = comrak::ComrakOptions::default();
+    options.parse.smart = opts.smart;
+    options.extension.table = true;
+    options.extension.tasklist = true;
+    options.extension.strikethrough = true;
+    options.extension.description_lists = true;
+    options.extension.footnotes = true;
+    options
+}
+
+/// `src` of a text node with its emoji shortcodes, and `$...$` math if it's turned on
+fn prose(src: &str, opts: &ParseOptions) -> String {
+    if opts.math {
+        emoji::expand(&math::inline(src))
+    } else {
+        emoji::expand(src)
+    }
+}
+
+/// all the text below `node` without its formatting, paragraphs on lines of their own
+fn node_text<'a>(node: &'a comrak::nodes::AstNode<'a>, opts: &ParseOptions) -> String {
+    let mut out = String::new();
+    for child in node.descendants() {
+        match &child.data.borrow().value {
+            NodeValue::Paragraph if !out.is_empty() => out.push('\n'),
+            NodeValue::Text(src) => out.push_str(&prose(src, opts)),
+            NodeValue::Code(code) => out.push_str(&format!("`{}`", code.literal)),
+            NodeValue::SoftBreak => out.push(' '),
+            NodeValue::LineBreak => out.push('\n'),
+            _ => {}
+        }
+    }
+    out.trim().into()
+}
+
+/// settings that change how markdown is split and parsed into slides
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// a line consisting of only this text starts a new slide
+    pub separator: String,
+    pub html: HtmlMode,
+    /// columns between the tab stops tabs in code blocks are expanded to
+    pub tab_width: usize,
+    /// curly quotes, dashes and ellipses in text, code is left alone
+    pub smart: bool,
+    /// adds a contents slide listing the headings of all slides after the title slide
+    pub toc: bool,
+    /// gives the contents slide a number of its own in the slide counter
+    pub toc_counted: bool,
+    /// renders `$...$` in text as math, also turned on by `math: true` in the frontmatter
+    pub math: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            separator: "---".into(),
+            html: HtmlMode::default(),
+            tab_width: 4,
+            smart: false,
+            toc: false,
+            toc_counted: false,
+            math: false,
+        }
+    }
+}
+
+/// what happens to raw html in the markdown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum HtmlMode {
+    /// drop all of it, comments with directives included
+    Strip,
+    /// read directives from comments, break lines at `<br>` and keep the text
+    /// of other tags
+    #[default]
+    CommentDirectives,
+}
+
+/// the text of an html snippet, without comments and tags and with `<br>` as a newline
+fn html_text(html: &str) -> String {
+    let mut out = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let tag = &rest[start..];
+        let end = if tag.starts_with("<!--") {
+            tag.find("-->").map(|end| end + 3)
+        } else {
+            tag.find('>').map(|end| end + 1)
+        };
+        let Some(end) = end else {
+            rest = tag;
+            break;
+        };
+        let name = tag[1..end - 1].trim().trim_end_matches('/').trim();
+        if name.eq_ignore_ascii_case("br") {
+            out.push('\n');
+        }
+        rest = &tag[end..];
+    }
+    out.push_str(rest);
+    out.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// a paragraph of just this splits a slide into two columns
+const COLUMN_BREAK: &str = "---col---";
+
+/// whether a paragraph is the column break, also after `--smart` turned its dashes into `—`
+fn is_column_break(text: &str) -> bool {
+    matches!(text.trim(), COLUMN_BREAK | "—col—")
+}
+
+/// the items above the columns and those in the left and right one,
+/// headings at the top of a slide span both columns
+pub(crate) fn columns(items: &[SlideItem], split: usize) -> [&[SlideItem]; 3] {
+    let head = items[..split]
+        .iter()
+        .take_while(|item| matches!(item, SlideItem::Heading(..)))
+        .count();
+    [&items[..head], &items[head..split], &items[split..]]
+}
+
+/// a slide holding only this comment is kept even though it has nothing to show
+const BLANK_SLIDE: &str = "<!-- blank -->";
+
+/// value of a `<!-- key: value -->` comment
+fn directive<'a>(html: &'a str, key: &str) -> Option<&'a str> {
+    let comment = html.trim().strip_prefix("<!--")?.strip_suffix("-->")?;
+    let value = comment.trim().strip_prefix(key)?.strip_prefix(':')?.trim();
+    (!value.is_empty()).then_some(value)
+}
+
+/// splits the frontmatter off the top of `src`, a leading `---` block that
+/// isn't a yaml mapping is left alone as it's just a separator
+fn frontmatter(src: &str) -> Result<(Frontmatter, &str)> {
+    let Some(rest) = src
+        .strip_prefix("---\n")
+        .or_else(|| src.strip_prefix("---\r\n"))
+    else {
+        return Ok((Frontmatter::default(), src));
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let yaml = serde_yaml::from_str(&rest[..offset]);
+            let Ok(yaml @ serde_yaml::Value::Mapping(_)) = yaml else {
+                break;
+            };
+            let meta = serde_yaml::from_value(yaml).context("invalid frontmatter")?;
+            return Ok((meta, &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    Ok((Frontmatter::default(), src))
+}
+
+/// splits on lines that are exactly `separator`, ignoring ones inside code fences
+fn split_slides<'a>(md_slides: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut slides = vec![];
+    let mut start = 0;
+    let mut fences = Fences::default();
+    let mut offset = 0;
+    // a separator that could underline a setext heading does so right below text
+    let underline = !separator.is_empty()
+        && (separator.chars().all(|c| c == '-') || separator.chars().all(|c| c == '='));
+    let mut after_text = false;
+    for line in md_slides.split_inclusive('\n') {
+        let mut text = false;
+        if fences.line(line) {
+        } else if line.trim() == separator && !(underline && after_text) {
+            slides.push(&md_slides[start..offset]);
+            start = offset + line.len();
+        } else {
+            text = is_paragraph_line(line);
+        }
+        after_text = text;
+        offset += line.len();
+    }
+    slides.push(&md_slides[start..]);
+    slides
+}
+
+/// line numbers of the separators next to the slides of `src` with nothing on
+/// them, which are left out of the deck
+pub(crate) fn empty_slides(src: &str, separator: &str) -> Vec<usize> {
+    let Ok((_, body)) = frontmatter(src) else {
+        return vec![];
+    };
+    let line = |offset: usize| src[..src.len() - body.len() + offset].matches('\n').count();
+    let chunks = split_slides(body, separator);
+    let mut lines = vec![];
+    for (idx, chunk) in chunks.iter().enumerate() {
+        if !chunk.trim().is_empty() || chunks.len() == 1 {
+            continue;
+        }
+        let start = chunk.as_ptr() as usize - body.as_ptr() as usize;
+        // the separator after the slide, or before it for the last one
+        lines.push(if idx + 1 < chunks.len() {
+            line(start + chunk.len()) + 1
+        } else {
+            line(start)
+        });
+    }
+    lines
+}
+
+/// whether `line` is paragraph text, which a `---` or `===` line right below
+/// turns into a setext heading instead of following it
+fn is_paragraph_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    let indent = line.len() - line.trim_start().len();
+    let numbered = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .is_some_and(|end| end > 0 && trimmed[end..].starts_with(['.', ')']));
+    !trimmed.is_empty()
+        && indent < 4
+        && !numbered
+        && !trimmed.starts_with(['#', '>', '<', '|', '-', '*', '+', '=', '$'])
+}
+
+/// line number of a code fence that is opened but never closed
+pub(crate) fn unclosed_fence(src: &str) -> Option<usize> {
+    let mut fences = Fences::default();
+    let mut opened = None;
+    for (idx, line) in src.lines().enumerate() {
+        let was_open = fences.is_open();
+        if fences.line(line) && !was_open {
+            opened = Some(idx + 1);
+        }
+    }
+    opened.filter(|_| fences.is_open())
+}
+
+/// replaces lines of `<!-- include: part.md -->` with the file they name, read
+/// relative to `dir`, `stack` holds the files being expanded to catch cycles
+fn expand_includes(
+    src: &str,
+    dir: &Path,
+    stack: &mut BTreeSet<PathBuf>,
+    included: &mut BTreeSet<PathBuf>,
+) -> Result<String> {
+    let mut out = String::new();
+    let mut fences = Fences::default();
+    for line in src.split_inclusive('\n') {
+        if !fences.line(line) {
+            if let Some(include) = directive(line.trim(), "include") {
+                let path = dir.join(include);
+                let md = std::fs::read_to_string(&path).with_context(|| {
+                    format!("could not read included file '{}'", path.display())
+                })?;
+                let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+                if !stack.insert(key.clone()) {
+                    bail!("'{}' ends up including itself", path.display());
+                }
+                let md = expand_includes(&md, &file_dir(&path), stack, included)?;
+                stack.remove(&key);
+                included.insert(path);
+                out.push_str(&md);
+                if !md.ends_with('\n') {
+                    out.push('\n');
+                }
+                continue;
+            }
+        }
+        out.push_str(line);
+    }
+    Ok(out)
+}
+
+/// reads the files one after the other into a single deck, each starts a new slide
+pub fn mkslides(paths: &[impl AsRef<str>], opts: &ParseOptions) -> Result<Slides> {
+    let Some(first) = paths.first() else {
+        bail!("no slide files given");
+    };
+    let mut meta = None;
+    let mut slides = vec![];
+    let mut included = BTreeSet::new();
+    for path in paths {
+        let path = path.as_ref();
+        let md_slides = std::fs::read_to_string(path)
+            .with_context(|| format!("could not read slide file '{path}'"))?;
+        let (file_meta, md_slides) = frontmatter(&md_slides)?;
+        // only the first file's frontmatter describes the deck
+        let meta = meta.get_or_insert(file_meta);
+        let dir = file_dir(path);
+        let mut stack = BTreeSet::from([Path::new(path)
+            .canonicalize()
+            .unwrap_or_else(|_| path.into())]);
+        let md_slides = expand_includes(md_slides, &dir, &mut stack, &mut included)?;
+        let (md_slides, _) = expand_vars(&md_slides, meta);
+        slides.extend(parse_slides(&md_slides, &dir, &with_meta(opts, meta)));
+    }
+    // run hooks start from the first file's directory
+    let base_dir = file_dir(first.as_ref());
+    let mut slides = deck(meta.unwrap_or_default(), slides, first.as_ref(), &base_dir)?;
+    slides.includes = included.into_iter().collect();
+    if opts.toc {
+        slides.insert_toc(opts.toc_counted);
+    }
+    Ok(slides)
+}
+
+/// image paths are relative to the markdown file they're in
+fn file_dir(path: impl AsRef<Path>) -> PathBuf {
+    path.as_ref()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+pub fn mkslides_from_str(
+    md_slides: &str,
+    title: &str,
+    base_dir: impl AsRef<Path>,
+    opts: &ParseOptions,
+) -> Result<Slides> {
+    let base_dir = base_dir.as_ref();
+    let (meta, md_slides) = frontmatter(md_slides)?;
+    let mut included = BTreeSet::new();
+    let md_slides = expand_includes(md_slides, base_dir, &mut BTreeSet::new(), &mut included)?;
+    let (md_slides, _) = expand_vars(&md_slides, &meta);
+    let slides = parse_slides(&md_slides, base_dir, &with_meta(opts, &meta));
+    let mut slides = deck(meta, slides, title, base_dir)?;
+    slides.includes = included.into_iter().collect();
+    if opts.toc {
+        slides.insert_toc(opts.toc_counted);
+    }
+    Ok(slides)
+}
+
+/// `opts` with the settings the frontmatter turns on
+fn with_meta(opts: &ParseOptions, meta: &Frontmatter) -> ParseOptions {
+    ParseOptions {
+        math: opts.math || meta.math == Some(true),
+        ..opts.clone()
+    }
+}
+
+fn parse_slides(md_slides: &str, base_dir: &Path, opts: &ParseOptions) -> Vec<Slide> {
+    use comrak::{parse_document, Arena};
+    let arena = Arena::new();
+    split_slides(md_slides, &opts.separator)
+        .into_iter()
+        .map(|x| math::fence_display(x.trim()))
+        .map(|x| parse_document(&arena, &x, &options(opts)))
+        .filter_map(|node| {
+            let mut items = vec![];
+            let mut blank = false;
+            let mut id = None;
+            let mut align = None;
+            let mut bg = None;
+            let mut run = None;
+            let mut new = true;
+            // nested quotes are flattened into the outermost one
+            let mut quote_depth = 0;
+            // one entry per open list, holding the next number of ordered ones
+            let mut lists: Vec<Option<usize>> = vec![];
+            // target of the link the traversal is inside of
+            let mut link: Option<String> = None;
+            // one entry per open emphasis node
+            let mut emphasis: Vec<Modifier> = vec![];
+            // the text of terms, descriptions and footnotes is collected when they start
+            let mut collecting = false;
+            node.traverse().for_each(|node| {
+                let node = match node {
+                    NodeEdge::Start(node) => node,
+                    NodeEdge::End(node) => {
+                        let value = &node.data.borrow().value;
+                        match value {
+                            NodeValue::BlockQuote => quote_depth -= 1,
+                            NodeValue::List(_) => {
+                                lists.pop();
+                            }
+                            NodeValue::Link(_) => link = None,
+                            NodeValue::DescriptionList | NodeValue::FootnoteDefinition(_) => {
+                                collecting = false
+                            }
+                            NodeValue::Emph | NodeValue::Strong | NodeValue::Strikethrough => {
+                                emphasis.pop();
+                            }
                             _ => {}
+                        }
+                        // the text after an inline image goes on in a paragraph of its own
+                        // instead of the image's alt
+                        let image = matches!(value, NodeValue::Image(_));
+                        if (value.block() || image)
+                            && quote_depth == 0
+                            && lists.is_empty()
+                            && !collecting
+                        {
+                            new = true;
+                        }
+                        return;
+                    }
+                };
+                let modifier = emphasis.iter().fold(Modifier::empty(), |all, m| all | *m);
+                match &node.data.borrow().value {
+                    NodeValue::BlockQuote => {
+                        if quote_depth == 0 {
+                            items.push(SlideItem::Quote("".into()));
+                        }
+                        quote_depth += 1;
+                        new = false;
+                    }
+                    NodeValue::Paragraph if quote_depth > 0 => {
+                        if let Some(SlideItem::Quote(src)) = items.last_mut() {
+                            if !src.is_empty() {
+                                src.push('\n');
+                            }
+                        }
+                    }
+                    NodeValue::List(list) => {
+                        // println!("## LIST");
+                        if lists.is_empty() {
+                            items.push(SlideItem::Bullets(vec![]));
+                        }
+                        lists.push(match list.list_type {
+                            ListType::Ordered => Some(list.start),
+                            ListType::Bullet => None,
+                        });
+                        new = false;
+                    }
+                    NodeValue::Heading(heading) => {
+                        items.push(SlideItem::Heading(heading.level, vec![]));
+                        new = false;
+                    }
+                    NodeValue::HtmlBlock(_) | NodeValue::HtmlInline(_)
+                        if opts.html == HtmlMode::Strip => {}
+                    NodeValue::HtmlBlock(html) if html.literal.trim() == BLANK_SLIDE => {
+                        blank = true;
+                    }
+                    NodeValue::HtmlBlock(html) if directive(&html.literal, "id").is_some() => {
+                        id = directive(&html.literal, "id").map(Into::into);
+                    }
+                    NodeValue::HtmlBlock(html) if directive(&html.literal, "bg").is_some() => {
+                        let color = directive(&html.literal, "bg").map(Color::from_str);
+                        bg = color.and_then(Result::ok).or(bg);
+                    }
+                    NodeValue::HtmlBlock(html) if directive(&html.literal, "run").is_some() => {
+                        run = directive(&html.literal, "run").map(Into::into);
+                    }
+                    NodeValue::HtmlBlock(html) if directive(&html.literal, "align").is_some() => {
+                        align = match directive(&html.literal, "align") {
+                            Some("left") => Some(Alignment::Left),
+                            Some("center") => Some(Alignment::Center),
+                            Some("right") => Some(Alignment::Right),
+                            _ => align,
+                        };
+                    }
+                    value @ (NodeValue::HtmlBlock(_) | NodeValue::HtmlInline(_)) => {
+                        let text = match value {
+                            // blocks keep the indent of the html around the text
+                            NodeValue::HtmlBlock(html) => html_text(&html.literal)
+                                .lines()
+                                .map(str::trim)
+                                .join("\n")
+                                .trim()
+                                .into(),
+                            NodeValue::HtmlInline(html) => html_text(html),
+                            _ => String::new(),
+                        };
+                        if !text.is_empty() {
+                            if new {
+                                items.push(SlideItem::Paragraph(vec![]));
+                                new = false;
+                            }
+                            if let Some(item) = items.last_mut() {
+                                item.push_text(&text, modifier, link.as_deref());
+                            }
+                        }
+                    }
+
+                    NodeValue::ThematicBreak if quote_depth == 0 && lists.is_empty() => {
+                        items.push(SlideItem::Rule);
+                    }
+                    NodeValue::CodeBlock(codeblock) => {
+                        match codeblock.info.as_str() {
+                            info if info.split_whitespace().next() == Some("qrcode") => {
+                                items.push(SlideItem::QR(QrBlock::new(info, &codeblock.literal)));
+                            }
+                            "notes" => {
+                                items.push(SlideItem::Notes(codeblock.literal.clone()));
+                            }
+                            "math" => {
+                                items.push(SlideItem::Math(codeblock.literal.clone()));
+                            }
+                            info => {
+                                let mut code = CodeBlock::new(info, &codeblock.literal);
+                                code.expand_tabs(opts.tab_width);
+                                items.push(SlideItem::Code(code));
+                            }
+                        }
+                        new = false;
+                    }
+                    NodeValue::DescriptionList => {
+                        items.push(SlideItem::DefList(vec![]));
+                        collecting = true;
+                        new = false;
+                    }
+                    NodeValue::DescriptionTerm => {
+                        if let Some(SlideItem::DefList(entries)) = items.last_mut() {
+                            entries.push((node_text(node, opts), String::new()));
+                        }
+                    }
+                    NodeValue::DescriptionDetails => {
+                        if let Some(SlideItem::DefList(entries)) = items.last_mut() {
+                            if let Some((_, details)) = entries.last_mut() {
+                                if !details.is_empty() {
+                                    details.push('\n');
+                                }
+                                details.push_str(&node_text(node, opts));
+                            }
+                        }
+                    }
+                    // comrak numbers the definitions and moves them to the end in that order
+                    NodeValue::FootnoteDefinition(_) => {
+                        match items.last_mut() {
+                            Some(SlideItem::Footnotes(notes)) => notes.push(node_text(node, opts)),
+                            _ => items.push(SlideItem::Footnotes(vec![node_text(node, opts)])),
+                        }
+                        collecting = true;
+                        new = false;
+                    }
+                    NodeValue::FootnoteReference(number) => {
+                        if new {
+                            items.push(SlideItem::Paragraph(vec![]));
+                            new = false;
+                        }
+                        if let Some(item) = items.last_mut() {
+                            item.push_text(&footnote_marker(number), modifier, link.as_deref());
+                        }
+                    }
+                    NodeValue::Table(_) => {
+                        items.push(SlideItem::Table {
+                            headers: vec![],
+                            rows: vec![],
+                        });
+                        new = false;
+                    }
+                    NodeValue::TableRow(header) => {
+                        if let Some(SlideItem::Table { rows, .. }) = items.last_mut() {
+                            if !header {
+                                rows.push(vec![]);
+                            }
+                        }
+                        new = false;
+                    }
+                    NodeValue::TableCell => {
+                        if let Some(SlideItem::Table { headers, rows }) = items.last_mut() {
+                            match rows.last_mut() {
+                                Some(row) => row.push("".into()),
+                                None => headers.push("".into()),
+                            }
+                        }
+                        new = false;
+                    }
+                    NodeValue::Image(link) => {
+                        // the image splits the paragraph it's in, without the space around it
+                        if let (false, Some(SlideItem::Paragraph(runs))) = (new, items.last_mut()) {
+                            if let Some(run) = runs.last_mut() {
+                                run.text.truncate(run.text.trim_end().len());
+                            }
+                        }
+                        items.push(SlideItem::Image {
+                            path: image_path(base_dir, &link.url),
+                            alt: "".into(),
                         });
+                        new = false;
+                    }
+                    value @ (NodeValue::Item(_) | NodeValue::TaskItem(_)) => {
+                        // println!("## ITEM");
+                        let checked = match value {
+                            NodeValue::TaskItem(symbol) => Some(symbol.is_some()),
+                            _ => None,
+                        };
+                        if let Some(SlideItem::Bullets(bullets)) = items.last_mut() {
+                            let number = lists.last_mut().and_then(|next| {
+                                let number = *next;
+                                *next = next.map(|n| n + 1);
+                                number
+                            });
+                            bullets.push(Bullet {
+                                depth: lists.len().saturating_sub(1),
+                                number,
+                                checked,
+                                text: vec![],
+                                hidden: false,
+                            });
+                        }
+                        new = false;
+                    }
+                    NodeValue::Code(code) => {
+                        if new {
+                            items.push(SlideItem::Paragraph(vec![]));
+                            new = false;
+                        }
+                        if let Some(item) = items.last_mut() {
+                            item.push_code(&code.literal, modifier, link.as_deref());
+                        }
+                    }
+                    // the space left between two images
+                    NodeValue::Text(src) if new && src.trim().is_empty() => {}
+                    NodeValue::Text(src) => {
+                        // println!("{src}");
+                        let src = match new {
+                            true => {
+                                items.push(SlideItem::Paragraph(vec![]));
+                                new = false;
+                                src.trim_start()
+                            }
+                            false => src.as_str(),
+                        };
+                        if let Some(item) = items.last_mut() {
+                            item.push_text(&prose(src, opts), modifier, link.as_deref());
+                        }
+                    }
+                    NodeValue::SoftBreak if !new => {
+                        if let Some(item) = items.last_mut() {
+                            item.push_text(" ", modifier, link.as_deref());
+                        }
                     }
+                    // two trailing spaces or a `\` keep the line break
+                    NodeValue::LineBreak if !new => {
+                        if let Some(item) = items.last_mut() {
+                            item.push_text("\n", modifier, link.as_deref());
+                        }
+                    }
+                    NodeValue::Link(target) => link = Some(target.url.clone()),
+                    NodeValue::Emph => emphasis.push(Modifier::ITALIC),
+                    NodeValue::Strong => emphasis.push(Modifier::BOLD),
+                    NodeValue::Strikethrough => emphasis.push(Modifier::CROSSED_OUT),
                     _ => {}
                 };
             });
-            Slide {
-                title: path.as_ref().into(),
-                items,
+            let column_break = items.iter().position(
+                |item| matches!(item, SlideItem::Paragraph(runs) if is_column_break(&plain(runs))),
+            );
+            if let Some(idx) = column_break {
+                items.remove(idx);
             }
+            // stray separators leave empty slides behind, only keep asked for ones
+            (!items.is_empty() || blank || id.is_some() || run.is_some()).then(|| Slide {
+                title: String::new(),
+                id,
+                align,
+                column_break,
+                bg,
+                run,
+                items,
+                part: 0,
+                toc: false,
+            })
         })
-        .collect::<Vec<_>>();
+        .collect()
+}
+
+/// `title` names the deck when neither the frontmatter nor the first heading do
+pub(crate) fn deck(
+    meta: Frontmatter,
+    mut slides: Vec<Slide>,
+    title: &str,
+    base_dir: &Path,
+) -> Result<Slides> {
     // println!("{slides:?}");
+    if slides.is_empty() {
+        bail!("no slides found in '{title}'");
+    }
+    // the path is only used when nothing better names the deck
+    let title = meta
+        .title
+        .clone()
+        .or_else(|| slides[0].heading())
+        .unwrap_or_else(|| title.into());
+    for slide in &mut slides {
+        slide.title = slide.heading().unwrap_or_else(|| title.clone());
+    }
     Ok(Slides {
-        title: path.as_ref().into(),
+        title,
+        meta,
         slides,
         current_idx: 0,
+        revealed: None,
+        wrap: false,
+        toc_counted: false,
+        scroll: BTreeMap::new(),
+        base_dir: base_dir.to_owned(),
+        includes: vec![],
     })
 }
 
-pub(crate) fn render_slide<B: ratatui::backend::Backend>(
-    slide: &Slide,
-) -> Box<dyn FnOnce(&mut Frame<B>)> {
-    let src = slide.title.clone();
-    let items = slide.items.clone();
-    Box::new(move |frame| {
+/// where the item stack sits vertically on the slide
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum VerticalAlign {
+    #[default]
+    Top,
+    Center,
+}
+
+/// where the title bar is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum TitleBar {
+    #[default]
+    Top,
+    /// on the last row, behind the slide counter
+    Bottom,
+    #[value(skip)]
+    Hidden,
+}
+
+/// what the title bar says
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum TitleContent {
+    /// the heading of the slide, or the deck title if it has none
+    #[default]
+    Slide,
+    /// the deck title on every slide
+    Deck,
+    /// the `title_bar` key of the frontmatter, or the deck title without one
+    Frontmatter,
+}
+
+/// settings that change how slides are drawn
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RenderOptions {
+    pub(crate) vertical_align: VerticalAlign,
+    pub(crate) theme: Theme,
+    /// show the next slide, speaker notes and a timer next to the current slide
+    pub(crate) presenter: bool,
+    /// soft wrap code lines wider than the slide instead of cutting them off
+    pub(crate) code_wrap: bool,
+    /// number the lines of every code block
+    pub(crate) code_line_numbers: bool,
+    /// one column per QR module instead of two
+    pub(crate) qr_narrow: bool,
+    /// animation between slides, `None` switches instantly
+    pub(crate) transition: Option<Transition>,
+    pub(crate) margin: Margin,
+    /// offset of the local time shown in the corner, `None` hides the clock
+    pub(crate) clock: Option<UtcOffset>,
+    /// syntax highlighting colors are picked to be readable on this
+    pub(crate) code_background: Background,
+    pub(crate) image_mode: ImageMode,
+    pub(crate) title_bar: TitleBar,
+    /// keeps slides that don't fit in one piece to be scrolled instead of splitting them
+    pub(crate) scroll: bool,
+    pub(crate) title_content: TitleContent,
+}
+
+/// blank space around the slide content, the title bar sits at the top margin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Margin {
+    /// columns left empty on both the left and the right
+    pub(crate) sides: u16,
+    /// rows above the title bar, the progress bar is drawn in the first one
+    pub(crate) top: u16,
+}
+
+impl Default for Margin {
+    fn default() -> Self {
+        Self { sides: 4, top: 1 }
+    }
+}
+
+/// `4` sets the sides only, `4,2` the sides and the top
+impl FromStr for Margin {
+    type Err = String;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let number = |src: &str| {
+            src.trim()
+                .parse::<u16>()
+                .map_err(|_| format!("invalid margin '{src}', expected COLUMNS or COLUMNS,ROWS"))
+        };
+        Ok(match src.split_once(',') {
+            Some((sides, top)) => Self {
+                sides: number(sides)?,
+                top: number(top)?,
+            },
+            None => Self {
+                sides: number(src)?,
+                ..Self::default()
+            },
+        })
+    }
+}
+
+/// first row below the title bar where slide items start, right below the
+/// top margin if the bar isn't at the top
+fn content_top(area: Rect, opts: &RenderOptions) -> u16 {
+    match opts.title_bar {
+        TitleBar::Top => area.y + opts.margin.top + 3,
+        TitleBar::Bottom | TitleBar::Hidden => area.y + opts.margin.top + 1,
+    }
+}
+
+/// row past the last one items may use, the one below belongs to the footer
+fn content_bottom(area: Rect) -> u16 {
+    area.bottom().saturating_sub(1)
+}
+
+/// space left for an item starting at row `y`
+fn item_rect(area: Rect, y: u16, margin: Margin) -> Rect {
+    Rect {
+        x: area.x + margin.sides.min(area.width),
+        y,
+        width: area.width.saturating_sub(margin.sides * 2),
+        height: content_bottom(area).saturating_sub(y),
+    }
+}
+
+/// the part of the screen the current slide is drawn in
+pub(crate) fn slide_area(size: Rect, opts: &RenderOptions) -> Rect {
+    if opts.presenter {
+        presenter_layout(size)[0]
+    } else {
+        size
+    }
+}
+
+/// current slide on the left, next slide, notes and timer stacked on the right
+fn presenter_layout(size: Rect) -> [Rect; 4] {
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(size);
+    let side = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(50),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(cols[1]);
+    [cols[0], side[0], side[1], side[2]]
+}
+
+/// everything needed to draw one slide, detached from the deck
+struct SlideView<'a> {
+    title: Cow<'a, str>,
+    /// only copied when some bullets have to be hidden
+    items: Cow<'a, [SlideItem]>,
+    is_title_slide: bool,
+    align: Alignment,
+    column_break: Option<usize>,
+    bg: Option<Color>,
+    footer: String,
+    progress: f64,
+    /// rows scrolled down, cut to how far the slide overflows when drawn
+    scroll: u16,
+}
+
+impl<'a> SlideView<'a> {
+    /// `revealed` limits how many bullets are drawn, counting across all lists
+    fn new(
+        slides: &'a Slides,
+        idx: usize,
+        revealed: Option<usize>,
+        content: TitleContent,
+    ) -> Option<Self> {
+        let slide = slides.slides.get(idx)?;
+        let mut items = Cow::Borrowed(slide.items.as_slice());
+        if let Some(revealed) = revealed {
+            let bullets = items.to_mut().iter_mut().flat_map(|item| match item {
+                SlideItem::Bullets(bullets) => bullets.as_mut_slice(),
+                _ => &mut [],
+            });
+            for bullet in bullets.skip(revealed) {
+                bullet.hidden = true;
+            }
+        }
+        let title = match content {
+            TitleContent::Slide => Cow::Borrowed(slide.title.as_str()),
+            TitleContent::Deck => Cow::Borrowed(slides.title.as_str()),
+            TitleContent::Frontmatter => match slides.meta.var("title_bar") {
+                Some(title) => Cow::Owned(title),
+                None => Cow::Borrowed(slides.title.as_str()),
+            },
+        };
+        let title = match &slides.meta.author {
+            Some(author) => Cow::Owned(format!("{title} · {author}")),
+            None => title,
+        };
+        Some(Self {
+            title,
+            items,
+            // the first page of a split slide may look like a lone heading
+            is_title_slide: slide.is_title_slide()
+                && slides.slides.get(idx + 1).is_none_or(|next| next.part == 0),
+            align: slide.align.unwrap_or(Alignment::Left),
+            column_break: slide.column_break,
+            bg: slide.bg,
+            footer: slides.counter(idx),
+            progress: slides.progress(idx),
+            scroll: slides.scroll.get(&idx).copied().unwrap_or(0),
+        })
+    }
+
+    fn render<B: ratatui::backend::Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        opts: &RenderOptions,
+    ) {
+        let theme = &opts.theme;
+        if let Some(bg) = self.bg {
+            // items draw on top without a background of their own, so the fill shows through
+            let fill = Style::default().bg(bg).fg(contrast(bg));
+            render_clipped(frame, area, Block::new().style(fill), area);
+        }
+        render_progress(frame, area, self.progress, theme);
+        let bar_y = match opts.title_bar {
+            TitleBar::Top => Some(area.y + opts.margin.top),
+            TitleBar::Bottom => Some(area.bottom().saturating_sub(1)),
+            TitleBar::Hidden => None,
+        };
+        if let Some(y) = bar_y {
+            render_clipped(
+                frame,
+                area,
+                Block::new()
+                    .title(self.title.as_ref())
+                    .fg(theme.title_fg)
+                    .bg(theme.title_bg)
+                    .title_alignment(Alignment::Center),
+                Rect {
+                    y,
+                    height: 1,
+                    ..area
+                },
+            );
+        }
+        render_footer(frame, area, &self.footer);
+        if self.items.is_empty() {
+            return;
+        }
+        if self.is_title_slide {
+            render_title_slide(frame, area, &self.items, theme);
+            return;
+        }
+        let top = content_top(area, opts);
+        let overflow = self.overflow(area, opts);
+        let rows = self.scroll.min(overflow);
+        let hidden = if rows == 0 {
+            self.render_body(frame, area, top, opts)
+        } else {
+            self.render_scrolled(frame, area, top, rows, opts);
+            rows < overflow
+        };
+        if rows > 0 {
+            render_more_above(frame, area, top, opts.margin);
+        }
+        if hidden {
+            render_more(frame, area, opts.margin);
+        }
+    }
+
+    /// draws the items from row `top` down, in two columns below the headings
+    /// if the slide has a column break, returns whether any didn't fit
+    fn render_body<B: ratatui::backend::Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        top: u16,
+        opts: &RenderOptions,
+    ) -> bool {
+        match self.column_break {
+            None => self.render_items(frame, area, top, &self.items, opts).1,
+            Some(split) => {
+                let [head, left, right] = columns(&self.items, split);
+                let (y, mut hidden) = self.render_items(frame, area, top, head, opts);
+                for (half, items) in halves(area).iter().zip([left, right]) {
+                    hidden |= self.render_items(frame, *half, y, items, opts).1;
+                }
+                hidden
+            }
+        }
+    }
+
+    /// draws the items `rows` rows further down on a taller offscreen frame and
+    /// copies the part between the title bar and the bottom row into view
+    fn render_scrolled<B: ratatui::backend::Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        top: u16,
+        rows: u16,
+        opts: &RenderOptions,
+    ) {
+        let tall = Rect {
+            height: area.height + rows,
+            ..area
+        };
+        let Ok(mut terminal) = Terminal::new(TestBackend::new(tall.right(), tall.bottom())) else {
+            return;
+        };
+        let images = graphics::queued();
+        let links = hyperlink::queued();
+        let drawn = terminal.draw(|offscreen| {
+            if let Some(bg) = self.bg {
+                let fill = Style::default().bg(bg).fg(contrast(bg));
+                render_clipped(offscreen, tall, Block::new().style(fill), tall);
+            }
+            self.render_body(offscreen, tall, top, opts);
+        });
+        let visible = Rect {
+            y: top,
+            height: content_bottom(area).saturating_sub(top),
+            ..area
+        };
+        // images and links were queued where the taller frame has them
+        graphics::scroll(images, rows, visible);
+        hyperlink::scroll(links, rows, visible);
+        if drawn.is_ok() {
+            let window = Window {
+                buf: terminal.backend().buffer(),
+                rows,
+            };
+            render_clipped(frame, area, window, visible);
+        }
+    }
+
+    /// rows the items run past the bottom of `area`, 0 if they fit
+    fn overflow(&self, area: Rect, opts: &RenderOptions) -> u16 {
+        if self.is_title_slide {
+            return 0;
+        }
+        let top = content_top(area, opts);
+        let height = |area: Rect, y: u16, items: &[SlideItem]| {
+            items
+                .iter()
+                .map(|item| item.height(item_rect(area, y, opts.margin), opts))
+                .sum::<u16>()
+        };
+        let end = match self.column_break {
+            None => top + height(area, top, &self.items),
+            Some(split) => {
+                let [head, left, right] = columns(&self.items, split);
+                let y = top + height(area, top, head);
+                let [left_half, right_half] = [halves(area)[0], halves(area)[1]];
+                y + height(left_half, y, left).max(height(right_half, y, right))
+            }
+        };
+        // the gap below the last item may run past the bottom
+        end.saturating_sub(2).saturating_sub(content_bottom(area))
+    }
+
+    /// draws `items` one below the other from row `top` of `area`, returns the row
+    /// below the last one and whether any didn't fit
+    fn render_items<B: ratatui::backend::Backend>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        top: u16,
+        items: &[SlideItem],
+        opts: &RenderOptions,
+    ) -> (u16, bool) {
+        let mut prev_y = top;
+        if opts.vertical_align == VerticalAlign::Center {
+            let rect = item_rect(area, prev_y, opts.margin);
+            let total = items
+                .iter()
+                .map(|item| item.height(rect, opts))
+                .sum::<u16>();
+            prev_y += rect.height.saturating_sub(total) / 2;
+        }
+        let bottom = content_bottom(area);
+        let mut hidden = false;
+        for item in items {
+            let mut rect = item_rect(area, prev_y, opts.margin);
+            // footnotes sit at the bottom of the slide whatever is above them
+            if let SlideItem::Footnotes(_) = item {
+                let lines = item.height(rect, opts).saturating_sub(2);
+                prev_y = prev_y.max(bottom.saturating_sub(lines));
+                rect = item_rect(area, prev_y, opts.margin);
+            }
+            if prev_y >= bottom {
+                hidden |= item.height(rect, opts) > 0;
+                continue;
+            }
+            // narrowing the rect to the item keeps it laid out the same
+            let width = item.width(rect, opts);
+            let x = match self.align {
+                Alignment::Left => rect.x,
+                Alignment::Center => rect.x + (rect.width - width) / 2,
+                Alignment::Right => rect.x + rect.width - width,
+            };
+            let rect = match self.align {
+                Alignment::Left => rect,
+                _ => Rect { x, width, ..rect },
+            };
+            prev_y = item.render(frame, rect, opts);
+            // every visible item leaves a two row gap below itself
+            hidden |= prev_y > bottom + 2;
+        }
+        (prev_y, hidden)
+    }
+}
+
+pub(crate) fn render_slide<'a, B: ratatui::backend::Backend>(
+    slides: &'a Slides,
+    opts: &'a RenderOptions,
+    elapsed: Option<Duration>,
+    now: Option<Time>,
+) -> Result<SlideRender<'a, B>> {
+    let current = SlideView::new(
+        slides,
+        slides.current_idx,
+        slides.revealed,
+        opts.title_content,
+    )
+    .context("slides current failes")?;
+    if !opts.presenter {
+        return Ok(Box::new(move |frame| {
+            current.render(frame, frame.size(), opts);
+            if let Some(elapsed) = elapsed {
+                render_timer(frame, elapsed, opts.margin);
+            }
+            if let Some(now) = now {
+                render_time_of_day(frame, now, opts.margin);
+            }
+        }));
+    }
+    let next = SlideView::new(slides, slides.current_idx + 1, None, opts.title_content);
+    let notes = slides.current_notes();
+    Ok(Box::new(move |frame| {
+        let [current_area, next_area, notes_area, timer_area] = presenter_layout(frame.size());
+        current.render(frame, current_area, opts);
+        let next_block = Block::new().borders(Borders::ALL).title("next").dim();
+        let inner = next_block.inner(next_area);
+        frame.render_widget(next_block, next_area);
+        match &next {
+            Some(next) => next.render(frame, inner, opts),
+            None => frame.render_widget(
+                widgets::Paragraph::new("end of the deck").dim().italic(),
+                inner,
+            ),
+        }
         frame.render_widget(
-            Block::new()
-                .title(src.as_str())
-                .white()
-                .on_blue()
-                .title_alignment(Alignment::Center),
+            widgets::Paragraph::new(notes.as_str())
+                .wrap(Wrap { trim: false })
+                .block(Block::new().borders(Borders::ALL).title("notes")),
+            notes_area,
+        );
+        let timer = [
+            elapsed.map(|elapsed| format!("elapsed {}", clock(elapsed))),
+            now.map(|now| format!("time {}", time_of_day(now))),
+        ];
+        frame.render_widget(
+            widgets::Paragraph::new(timer.into_iter().flatten().join("   "))
+                .alignment(Alignment::Center),
+            timer_area,
+        );
+    }))
+}
+
+/// draws the current slide into a buffer of `area`'s size instead of the terminal
+pub(crate) fn render_offscreen(
+    slides: &Slides,
+    opts: &RenderOptions,
+    elapsed: Option<Duration>,
+    now: Option<Time>,
+    area: Rect,
+) -> Result<Buffer> {
+    let mut terminal = Terminal::new(TestBackend::new(area.width, area.height))?;
+    let render = render_slide(slides, opts, elapsed, now)?;
+    terminal.draw(|frame| render(frame))?;
+    // there's no terminal to send images or links to, the real frame
+    // queues its own afterwards
+    graphics::clear(&mut std::io::sink())?;
+    hyperlink::discard();
+    Ok(terminal.backend().buffer().clone())
+}
+
+/// draws `slide` on its own into a `width` by `height` buffer with the default
+/// options, so its layout can be checked without a terminal
+pub fn render_slide_to_buffer(slide: &Slide, width: u16, height: u16) -> Result<Buffer> {
+    let slides = Slides {
+        title: slide.title.clone(),
+        meta: Frontmatter::default(),
+        slides: vec![slide.clone()],
+        current_idx: 0,
+        revealed: None,
+        wrap: false,
+        toc_counted: false,
+        scroll: BTreeMap::new(),
+        base_dir: PathBuf::new(),
+        includes: vec![],
+    };
+    let area = Rect::new(0, 0, width, height);
+    render_offscreen(&slides, &RenderOptions::default(), None, None, area)
+}
+
+/// draws the elapsed time at the right end of the title row, inside the margins
+fn render_timer<B: ratatui::backend::Backend>(
+    frame: &mut Frame<B>,
+    elapsed: Duration,
+    margin: Margin,
+) {
+    let size = frame.size();
+    let clock = clock(elapsed);
+    let width = clock.width() as u16;
+    render_clipped(
+        frame,
+        size,
+        widgets::Paragraph::new(clock).dim(),
+        Rect {
+            x: size.width.saturating_sub(width + margin.sides.max(1)),
+            y: margin.top,
+            width,
+            height: 1,
+        },
+    );
+}
+
+/// draws the local time at the left end of the title row, across from the timer
+fn render_time_of_day<B: ratatui::backend::Backend>(
+    frame: &mut Frame<B>,
+    now: Time,
+    margin: Margin,
+) {
+    let size = frame.size();
+    let time = time_of_day(now);
+    render_clipped(
+        frame,
+        size,
+        widgets::Paragraph::new(time.as_str()).dim(),
+        Rect {
+            x: margin.sides.max(1).min(size.width),
+            y: margin.top,
+            width: time.width() as u16,
+            height: 1,
+        },
+    );
+}
+
+/// formats a time of day as `HH:MM`
+fn time_of_day(now: Time) -> String {
+    format!("{:02}:{:02}", now.hour(), now.minute())
+}
+
+/// formats a duration as `MM:SS`
+fn clock(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+/// draws the heading large in the middle of the area with the subtitle below it
+fn render_title_slide<B: ratatui::backend::Backend>(
+    frame: &mut Frame<B>,
+    area: Rect,
+    items: &[SlideItem],
+    theme: &Theme,
+) {
+    let mut texts = items.iter().filter_map(|item| match item {
+        SlideItem::Heading(_, runs) => Some(plain(runs)),
+        SlideItem::Paragraph(runs) => Some(plain(runs)),
+        _ => None,
+    });
+    let heading = texts.next().unwrap_or_default();
+    let subtitle = texts.next();
+    let y = area.height.saturating_sub(5) / 2;
+    let width = (heading.width() as u16 + 8).min(area.width);
+    render_clipped(
+        frame,
+        area,
+        widgets::Paragraph::new(heading)
+            .alignment(Alignment::Center)
+            .block(Block::new().padding(widgets::Padding::uniform(1)))
+            .style(
+                Style::default()
+                    .bg(theme.heading_bg)
+                    .fg(theme.heading_fg)
+                    .bold(),
+            ),
+        Rect {
+            x: area.x + area.width.saturating_sub(width) / 2,
+            y: area.y + y,
+            width,
+            height: 3,
+        },
+    );
+    if let Some(subtitle) = subtitle {
+        render_clipped(
+            frame,
+            area,
+            widgets::Paragraph::new(subtitle)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true })
+                .italic(),
             Rect {
-                x: 0,
-                y: 1,
-                width: src.len() as u16 + 2,
-                height: 1,
+                y: area.y + y + 4,
+                ..area
             },
         );
-        // for item in items {
-        if items.len() == 0 {
-            return;
+    }
+}
+
+/// marks that some of the slide didn't fit in its area
+fn render_more<B: ratatui::backend::Backend>(frame: &mut Frame<B>, area: Rect, margin: Margin) {
+    let more = "▼ more";
+    render_clipped(
+        frame,
+        area,
+        widgets::Paragraph::new(more).dim(),
+        Rect {
+            x: area.x + margin.sides.min(area.width),
+            y: area.bottom().saturating_sub(1),
+            width: more.width() as u16,
+            height: 1,
+        },
+    );
+}
+
+/// the same marker as [`render_more`] above the first row of a scrolled slide
+fn render_more_above<B: ratatui::backend::Backend>(
+    frame: &mut Frame<B>,
+    area: Rect,
+    top: u16,
+    margin: Margin,
+) {
+    let more = "▲ more";
+    render_clipped(
+        frame,
+        area,
+        widgets::Paragraph::new(more).dim(),
+        Rect {
+            x: area.x + margin.sides.min(area.width),
+            y: top.saturating_sub(1),
+            width: more.width() as u16,
+            height: 1,
+        },
+    );
+}
+
+/// the left and right column of a slide split with `---col---`
+fn halves(area: Rect) -> Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area)
+}
+
+/// copies the cells of an offscreen buffer `rows` rows below where they're drawn
+struct Window<'a> {
+    buf: &'a Buffer,
+    rows: u16,
+}
+
+impl widgets::Widget for Window<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                *buf.get_mut(x, y) = self.buf.get(x, y + self.rows).clone();
+            }
         }
-        let mut prev_y = 4;
-        for item in &items {
-            prev_y = item.render(
+    }
+}
+
+/// draws a transient message on the bottom row
+pub(crate) fn render_status<B: ratatui::backend::Backend>(frame: &mut Frame<B>, msg: &str) {
+    let size = frame.size();
+    frame.render_widget(
+        widgets::Paragraph::new(msg).red(),
+        Rect {
+            x: 1.min(size.width),
+            y: size.height.saturating_sub(1),
+            width: (msg.width() as u16).min(size.width.saturating_sub(1)),
+            height: 1.min(size.height),
+        },
+    );
+}
+
+/// draws the command being typed after its `prompt` in the bottom left corner
+pub(crate) fn render_command<B: ratatui::backend::Backend>(
+    frame: &mut Frame<B>,
+    prompt: char,
+    cmd: &str,
+) {
+    let size = frame.size();
+    let cmd = format!("{prompt}{cmd}");
+    render_clipped(
+        frame,
+        size,
+        widgets::Paragraph::new(cmd.as_str()).reversed(),
+        Rect {
+            x: 1.min(size.width),
+            y: size.height.saturating_sub(1),
+            width: cmd.width() as u16,
+            height: 1,
+        },
+    );
+}
+
+/// columns of thumbnails the overview fits into `width`
+pub(crate) fn overview_columns(width: u16) -> usize {
+    (width / 24).max(1) as usize
+}
+
+/// draws item `item` of the current slide on its own over the whole frame,
+/// with its place among the zoomable items in the corner
+pub(crate) fn render_zoomed<B: ratatui::backend::Backend>(
+    frame: &mut Frame<B>,
+    slides: &Slides,
+    item: usize,
+    opts: &RenderOptions,
+) {
+    let items = &slides.slides[slides.current_idx].items;
+    let Some(selected) = items.get(item).filter(|item| item.zoomable()) else {
+        return;
+    };
+    let size = frame.size();
+    let margin = Margin { sides: 1, top: 0 };
+    let rect = item_rect(size, size.y + 1, margin);
+    // the gap below the item isn't needed, there's nothing after it
+    let height = selected.height(rect, opts).saturating_sub(2);
+    let rect = item_rect(
+        size,
+        rect.y + rect.height.saturating_sub(height) / 2,
+        margin,
+    );
+    let width = selected.width(rect, opts);
+    selected.render(
+        frame,
+        Rect {
+            x: rect.x + (rect.width - width) / 2,
+            width,
+            ..rect
+        },
+        opts,
+    );
+    let zoomable = slides.zoomable();
+    let pos = zoomable.iter().position(|&idx| idx == item).unwrap_or(0);
+    render_footer(
+        frame,
+        size,
+        &format!("zoom {} / {}", pos + 1, zoomable.len()),
+    );
+}
+
+/// draws every slide as a small box with its text, `selected` is highlighted
+/// and the rows scroll to keep it on screen
+pub(crate) fn render_overview<B: ratatui::backend::Backend>(
+    frame: &mut Frame<B>,
+    slides: &Slides,
+    selected: usize,
+    theme: &Theme,
+) {
+    const THUMB_HEIGHT: u16 = 7;
+    let size = frame.size();
+    let cols = overview_columns(size.width);
+    let visible_rows = (size.height / THUMB_HEIGHT).max(1) as usize;
+    let first_row = (selected / cols).saturating_sub(visible_rows - 1);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(THUMB_HEIGHT); visible_rows])
+        .split(size);
+    for (row_idx, row) in rows.iter().enumerate() {
+        let cells = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, cols as u32); cols])
+            .split(*row);
+        for (col_idx, cell) in cells.iter().enumerate() {
+            let idx = (first_row + row_idx) * cols + col_idx;
+            let Some(slide) = slides.slides.get(idx) else {
+                return;
+            };
+            let block = Block::new()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", idx + 1));
+            let block = if idx == selected {
+                block.border_style(Style::default().fg(theme.title_bg).bold())
+            } else {
+                block.dim()
+            };
+            let mut lines = slide
+                .outline()
+                .into_iter()
+                .flat_map(|text| text.lines().map(str::to_owned).collect::<Vec<_>>());
+            let name = lines.next().unwrap_or_default();
+            let text = std::iter::once(Line::from(Span::raw(name).bold()))
+                .chain(lines.map(|line| Line::from(Span::raw(line).dim())))
+                .collect::<Vec<_>>();
+            render_clipped(
                 frame,
-                Rect {
-                    x: 4,
-                    y: prev_y,
-                    width: frame.size().width - 8,
-                    height: frame.size().height - prev_y,
-                },
+                size,
+                widgets::Paragraph::new(text).block(block),
+                *cell,
             );
         }
-    })
+    }
+}
+
+/// draws the key bindings in a box in the middle of the screen
+pub(crate) fn render_help<B: ratatui::backend::Backend>(
+    frame: &mut Frame<B>,
+    rows: &[(String, &str)],
+) {
+    let keys_width = rows.iter().map(|(keys, _)| keys.width()).max().unwrap_or(0);
+    let lines = rows
+        .iter()
+        .map(|(keys, what)| {
+            Line::from(vec![
+                Span::raw(format!("{keys:>keys_width$}")).bold(),
+                Span::raw(format!("  {what}")),
+            ])
+        })
+        .collect::<Vec<_>>();
+    let size = frame.size();
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let area = Rect {
+        x: size.width.saturating_sub(width) / 2,
+        y: size.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    render_clipped(frame, size, widgets::Clear, area);
+    render_clipped(
+        frame,
+        size,
+        widgets::Paragraph::new(lines).block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("keys")
+                .padding(widgets::Padding::horizontal(1)),
+        ),
+        area,
+    );
+}
+
+/// the `current / total` counter of slide `idx`, the same live and in exports
+pub(crate) fn slide_counter(idx: usize, count: usize) -> String {
+    format!("{} / {}", idx + 1, count)
+}
+
+/// how much of the progress bar is filled on slide `idx`
+pub(crate) fn progress(idx: usize, count: usize) -> f64 {
+    (idx + 1) as f64 / count as f64
+}
+
+/// draws a one row bar across the top filled up to the current slide
+fn render_progress<B: ratatui::backend::Backend>(
+    frame: &mut Frame<B>,
+    area: Rect,
+    progress: f64,
+    theme: &Theme,
+) {
+    render_clipped(
+        frame,
+        area,
+        widgets::Gauge::default()
+            .gauge_style(Style::default().fg(theme.title_bg))
+            .ratio(progress.clamp(0.0, 1.0))
+            .label("")
+            .use_unicode(true),
+        Rect { height: 1, ..area },
+    );
+}
+
+/// draws the `current / total` slide counter in the bottom-right corner
+fn render_footer<B: ratatui::backend::Backend>(frame: &mut Frame<B>, area: Rect, footer: &str) {
+    let width = (footer.width() as u16).min(area.width);
+    render_clipped(
+        frame,
+        area,
+        widgets::Paragraph::new(footer).alignment(Alignment::Right),
+        Rect {
+            x: area.right().saturating_sub(width + 1),
+            y: area.bottom().saturating_sub(1),
+            width,
+            height: 1,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_separator_lines() {
+        assert_eq!(
+            split_slides("# a\n\n---\n\n# b\n", "---"),
+            ["# a\n\n", "\n# b\n"]
+        );
+        assert_eq!(split_slides("a\n\n***\n\nb\n", "***"), ["a\n\n", "\nb\n"]);
+    }
+
+    #[test]
+    fn setext_underlines_dont_split() {
+        let md = "Title\n---\n\ntext\n\n---\n\nnext\n";
+        assert_eq!(
+            split_slides(md, "---"),
+            ["Title\n---\n\ntext\n\n", "\nnext\n"]
+        );
+        // a list item or heading above isn't paragraph text, so the line separates
+        assert_eq!(split_slides("- item\n---\nnext\n", "---").len(), 2);
+        assert_eq!(split_slides("# a\n---\nb\n", "---").len(), 2);
+    }
+
+    #[test]
+    fn separators_in_fences_dont_split() {
+        let md = "```yaml\n---\nkey: 1\n```\n\n---\n\n~~~\n---\n~~~\n";
+        assert_eq!(
+            split_slides(md, "---"),
+            ["```yaml\n---\nkey: 1\n```\n\n", "\n~~~\n---\n~~~\n"]
+        );
+    }
+
+    #[test]
+    fn only_matching_fences_close() {
+        let md = "# A\n\n````md\n```rust\n---\n```\n````\n\n---\n\n# B\n";
+        assert_eq!(split_slides(md, "---").len(), 2);
+        // a fence with an info string opens a block, it never closes one
+        let md = "# A\n\n```\n```rust\n---\n```\n\n---\n\n# B\n";
+        assert_eq!(split_slides(md, "---").len(), 2);
+    }
+
+    #[test]
+    fn image_urls_stay_urls() {
+        let dir = Path::new("deck");
+        let url = "https://example.com/logo.png";
+        assert_eq!(image_path(dir, url), PathBuf::from(url));
+        assert_eq!(image_path(dir, "a.png"), dir.join("a.png"));
+        assert_eq!(image_text(Path::new(url), "logo"), "[image: logo]");
+        assert_eq!(image_text(Path::new(url), ""), format!("[image: {url}]"));
+        assert!(!is_url(&dir.join("a.png")));
+    }
+
+    #[test]
+    fn finds_unclosed_fences() {
+        assert_eq!(unclosed_fence("a\n```\ncode\n```\n"), None);
+        assert_eq!(unclosed_fence("a\n\n````\n```\n"), Some(3));
+    }
+
+    /// a fresh directory holding `files`, removed by the caller
+    fn deck_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mkslides-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, src) in files {
+            std::fs::write(dir.join(file), src).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn expands_includes() {
+        let dir = deck_dir(
+            "include",
+            &[
+                ("main.md", "# A\n\n<!-- include: part.md -->\n"),
+                (
+                    "part.md",
+                    "from the part\n\n```\n<!-- include: main.md -->\n```\n",
+                ),
+            ],
+        );
+        let main = dir.join("main.md").display().to_string();
+        let slides = mkslides(&[&main], &ParseOptions::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let slides = slides.unwrap();
+        assert_eq!(slides.includes(), [dir.join("part.md")]);
+        assert!(matches!(
+            &slides.slides[0].items[1],
+            SlideItem::Paragraph(runs) if plain(runs) == "from the part"
+        ));
+    }
+
+    #[test]
+    fn include_cycles_are_errors() {
+        let dir = deck_dir(
+            "cycle",
+            &[
+                ("a.md", "# A\n\n<!-- include: b.md -->\n"),
+                ("b.md", "<!-- include: a.md -->\n"),
+            ],
+        );
+        let a = dir.join("a.md").display().to_string();
+        let slides = mkslides(&[&a], &ParseOptions::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let err = slides.unwrap_err().to_string();
+        assert!(err.ends_with("a.md' ends up including itself"), "{err}");
+    }
+
+    #[test]
+    fn code_block_options() {
+        let code = CodeBlock::new("rust,linenos {2,4-5}", "a\nb\nc\nd\ne\n");
+        assert_eq!(code.lang, "rust");
+        assert!(code.line_numbers);
+        assert_eq!(code.highlight, BTreeSet::from([1, 3, 4]));
+        assert_eq!(code.info(), "rust,linenos {2,4-5}");
+
+        let code = CodeBlock::new("python", "a\n");
+        assert_eq!(code.lang, "python");
+        assert!(!code.line_numbers);
+        assert!(code.highlight.is_empty());
+    }
+
+    #[test]
+    fn code_block_line_specs_stay_in_the_source() {
+        let code = CodeBlock::new("rust {0-2, x, 3-100000000}", "a\nb\nc\nd\n");
+        assert_eq!(code.highlight, BTreeSet::from([0, 1, 2, 3]));
+        assert!(code.inverted.is_empty());
+
+        let code = CodeBlock::new("rust {3-1}", "a\nb\nc\n");
+        assert!(code.highlight.is_empty());
+        assert_eq!(code.inverted, ["3-1"]);
+    }
+
+    /// the numbers on the contents slide's entries
+    fn toc_numbers(slides: &Slides) -> Vec<Option<usize>> {
+        slides
+            .slides
+            .iter()
+            .filter(|slide| slide.toc)
+            .flat_map(|slide| &slide.items)
+            .flat_map(|item| match item {
+                SlideItem::Bullets(bullets) => bullets.iter().map(|b| b.number).collect(),
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    fn long_deck(opts: &ParseOptions) -> Slides {
+        let bullets = (1..=20).map(|n| format!("- point {n}\n")).join("");
+        let md = format!(
+            "# one\n\nintro\n\n- a\n\n---\n\n# two\n\n{bullets}\n---\n\n# three\n\nend\n\n- c\n"
+        );
+        mkslides_from_str(&md, "deck", "", opts).unwrap()
+    }
+
+    #[test]
+    fn toc_follows_pagination() {
+        let opts = ParseOptions {
+            toc: true,
+            toc_counted: true,
+            ..ParseOptions::default()
+        };
+        let mut slides = long_deck(&opts);
+        assert!(slides.slides[0].toc);
+        assert_eq!(toc_numbers(&slides), [Some(2), Some(3), Some(4)]);
+
+        slides.paginate(Rect::new(0, 0, 40, 20), &RenderOptions::default());
+        let pages = slides.slides.iter().filter(|slide| slide.part > 0);
+        assert!(pages.clone().all(|page| page.title == "two"));
+        let pages = pages.count();
+        assert!(pages > 0);
+        assert_eq!(toc_numbers(&slides), [Some(2), Some(3), Some(4 + pages)]);
+        assert_eq!(slides.counter(0), slide_counter(0, 4 + pages));
+
+        // undone when the slides fit again
+        slides.paginate(Rect::new(0, 0, 80, 60), &RenderOptions::default());
+        assert_eq!(toc_numbers(&slides), [Some(2), Some(3), Some(4)]);
+    }
+
+    #[test]
+    fn uncounted_toc_takes_no_number() {
+        let opts = ParseOptions {
+            toc: true,
+            ..ParseOptions::default()
+        };
+        let mut slides = long_deck(&opts);
+        assert_eq!(toc_numbers(&slides), [Some(1), Some(2), Some(3)]);
+        assert_eq!(slides.counter(0), "contents");
+        assert_eq!(slides.counter(1), slide_counter(0, 3));
+
+        slides.paginate(Rect::new(0, 0, 40, 20), &RenderOptions::default());
+        let pages = slides.slides.iter().filter(|slide| slide.part > 0).count();
+        assert_eq!(toc_numbers(&slides), [Some(1), Some(2), Some(3 + pages)]);
+    }
+
+    #[test]
+    fn empty_decks_dont_panic() {
+        let opts = ParseOptions::default();
+        let mut slides =
+            mkslides_from_str("# A\n\n```rust\nfn a() {}\n```\n", "", "", &opts).unwrap();
+        slides.slides.clear();
+        slides.set_wrap(true);
+        slides.prev();
+        slides.next();
+        assert_eq!(slides.current_code(None), None);
+        assert!(slides.zoomable().is_empty());
+    }
+
+    #[test]
+    fn renders_to_a_buffer() {
+        let src = "# Hello\n\nsome text\n\n---\n\n## Two\n\n---\n\n## Three\n";
+        let slides = mkslides_from_str(src, "", "", &ParseOptions::default()).unwrap();
+        let (width, height) = (40, 12);
+        let buffer = render_slide_to_buffer(&slides.slides()[0], width, height).unwrap();
+        let row = |y| {
+            (0..width)
+                .map(|x| buffer.get(x, y).symbol.as_str())
+                .collect::<String>()
+        };
+        // the title bar names the deck, the heading sits below it in bold
+        assert_eq!(row(1).trim(), "Hello");
+        let y = (2..height).find(|&y| row(y).trim() == "Hello").unwrap();
+        let x = row(y).find('H').unwrap() as u16;
+        for x in x..x + 5 {
+            assert!(buffer.get(x, y).modifier.contains(Modifier::BOLD));
+        }
+        assert!((y + 1..height).any(|y| row(y).trim() == "some text"));
+        // the slide is drawn on its own, so it counts as the only one
+        assert_eq!(row(height - 1).trim(), "1 / 1");
+    }
 }
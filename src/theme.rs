@@ -0,0 +1,252 @@
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer};
+
+/// colors used to draw slides, loaded from `<config dir>/mkslides/theme.toml`
+///
+/// colors are written like `red`, `lightblue`, `#ff8800` or an ansi index like `208`,
+/// any field left out keeps its default
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub(crate) struct Theme {
+    #[serde(deserialize_with = "color")]
+    pub(crate) heading_fg: Color,
+    #[serde(deserialize_with = "color")]
+    pub(crate) heading_bg: Color,
+    /// text of `##` headings, they share the background of the top level ones
+    #[serde(deserialize_with = "color")]
+    pub(crate) heading2_fg: Color,
+    /// text of `###` and lower headings, drawn without a background
+    #[serde(deserialize_with = "color")]
+    pub(crate) heading3_fg: Color,
+    #[serde(deserialize_with = "color")]
+    pub(crate) title_fg: Color,
+    #[serde(deserialize_with = "color")]
+    pub(crate) title_bg: Color,
+    #[serde(deserialize_with = "color")]
+    pub(crate) code_fg: Color,
+    #[serde(deserialize_with = "color")]
+    pub(crate) bullet_fg: Color,
+    /// background of the lines picked out with a `{2,4-6}` code block spec
+    #[serde(deserialize_with = "color")]
+    pub(crate) code_highlight_bg: Color,
+    /// colors of `code` inside a line of text
+    #[serde(deserialize_with = "color")]
+    pub(crate) inline_code_fg: Color,
+    #[serde(deserialize_with = "color")]
+    pub(crate) inline_code_bg: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            heading_fg: Color::White,
+            heading_bg: Color::Black,
+            heading2_fg: Color::LightCyan,
+            heading3_fg: Color::LightMagenta,
+            title_fg: Color::White,
+            title_bg: Color::Blue,
+            code_fg: Color::Reset,
+            bullet_fg: Color::Reset,
+            code_highlight_bg: Color::DarkGray,
+            inline_code_fg: Color::Yellow,
+            inline_code_bg: Color::Indexed(236),
+        }
+    }
+}
+
+impl Theme {
+    pub(crate) fn path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("mkslides").join("theme.toml"))
+    }
+
+    /// reads the theme from the config dir, the defaults are used if there is none
+    pub(crate) fn load() -> Result<Self> {
+        let Some(path) = Self::path().filter(|p| p.exists()) else {
+            return Ok(Self::default());
+        };
+        let src = std::fs::read_to_string(&path)
+            .with_context(|| format!("could not read theme '{}'", path.display()))?;
+        Self::from_toml(&src).with_context(|| format!("invalid theme '{}'", path.display()))
+    }
+
+    fn from_toml(src: &str) -> Result<Self> {
+        Ok(serde_yaml::from_value(toml_table(src)?.into())?)
+    }
+}
+
+/// reads a toml document of `key = value` lines, which is all a theme needs, as
+/// a mapping of strings so serde can fill in the theme from it
+fn toml_table(src: &str) -> Result<serde_yaml::Mapping> {
+    let mut table = serde_yaml::Mapping::new();
+    for (idx, line) in src.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            bail!(
+                "line {}: a theme has no tables, only `key = value` lines",
+                idx + 1
+            );
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("line {}: expected `key = value`", idx + 1);
+        };
+        let value = toml_value(value.trim())
+            .with_context(|| format!("line {}: expected a string or a number", idx + 1))?;
+        table.insert(key.trim().trim_matches('"').into(), value.into());
+    }
+    Ok(table)
+}
+
+/// a quoted string or a bare number, followed by nothing but a comment
+fn toml_value(src: &str) -> Option<String> {
+    let (value, rest) = match src.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let body = &src[1..];
+            let end = body.find(quote)?;
+            (body[..end].to_owned(), &body[end + 1..])
+        }
+        _ => {
+            let end = src.find('#').unwrap_or(src.len());
+            let number = src[..end].trim();
+            number.parse::<u32>().ok()?;
+            (number.to_owned(), "")
+        }
+    };
+    let rest = rest.trim();
+    (rest.is_empty() || rest.starts_with('#')).then_some(value)
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let src = String::deserialize(deserializer)?;
+    Color::from_str(&src).map_err(|_| serde::de::Error::custom(format!("unknown color '{src}'")))
+}
+
+/// writes `color` the way [`Color::from_str`] reads it back
+pub(crate) fn color_name(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{r:02x}{g:02x}{b:02x}"),
+        Color::Indexed(index) => index.to_string(),
+        color => format!("{color:?}").to_lowercase(),
+    }
+}
+
+/// black or white, whichever reads better on top of `bg`
+pub(crate) fn contrast(bg: Color) -> Color {
+    let light = match bg {
+        Color::Rgb(r, g, b) => 299 * r as u32 + 587 * g as u32 + 114 * b as u32 > 128_000,
+        Color::Gray | Color::White | Color::Yellow | Color::Cyan | Color::Green => true,
+        Color::LightRed
+        | Color::LightGreen
+        | Color::LightYellow
+        | Color::LightBlue
+        | Color::LightMagenta
+        | Color::LightCyan => true,
+        _ => false,
+    };
+    if light {
+        Color::Black
+    } else {
+        Color::White
+    }
+}
+
+/// rgb value of a terminal color, taking the xterm palette for the named ones
+pub(crate) fn rgb(color: Color, default: [u8; 3]) -> [u8; 3] {
+    const ANSI: [[u8; 3]; 16] = [
+        [0x00, 0x00, 0x00],
+        [0xcd, 0x00, 0x00],
+        [0x00, 0xcd, 0x00],
+        [0xcd, 0xcd, 0x00],
+        [0x00, 0x00, 0xee],
+        [0xcd, 0x00, 0xcd],
+        [0x00, 0xcd, 0xcd],
+        [0xe5, 0xe5, 0xe5],
+        [0x7f, 0x7f, 0x7f],
+        [0xff, 0x00, 0x00],
+        [0x00, 0xff, 0x00],
+        [0xff, 0xff, 0x00],
+        [0x5c, 0x5c, 0xff],
+        [0xff, 0x00, 0xff],
+        [0x00, 0xff, 0xff],
+        [0xff, 0xff, 0xff],
+    ];
+    let index = match color {
+        Color::Reset => return default,
+        Color::Rgb(r, g, b) => return [r, g, b],
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(index) => index,
+    };
+    match index {
+        0..=15 => ANSI[index as usize],
+        // a 6x6x6 color cube followed by 24 grays
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let n = index - 16;
+            [level(n / 36), level(n / 6 % 6), level(n % 6)]
+        }
+        _ => [8 + (index - 232) * 10; 3],
+    }
+}
+
+/// mixes `amount` of `to` into `from`, 0 keeps `from` and 1 gives `to`
+pub(crate) fn blend(from: [u8; 3], to: [u8; 3], amount: f32) -> [u8; 3] {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+    [
+        mix(from[0], to[0]),
+        mix(from[1], to[1]),
+        mix(from[2], to[2]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_toml() {
+        let theme = Theme::from_toml(
+            "# my colors\nheading_fg = \"red\"\nbullet_fg = '#ff8800' # orange\n\ntitle_fg = 15\n",
+        )
+        .unwrap();
+        assert_eq!(theme.heading_fg, Color::Red);
+        assert_eq!(theme.bullet_fg, Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(theme.title_fg, Color::Indexed(15));
+        // left out, so the default
+        assert_eq!(theme.title_bg, Color::Blue);
+    }
+
+    #[test]
+    fn rejects_what_it_cant_read() {
+        let err = |src: &str| format!("{:#}", Theme::from_toml(src).unwrap_err());
+        assert_eq!(err("title_fg = \"nope\""), "unknown color 'nope'");
+        assert_eq!(err("a = 1\ntitle_fg"), "line 2: expected `key = value`");
+        assert_eq!(
+            err("[colors]"),
+            "line 1: a theme has no tables, only `key = value` lines"
+        );
+        assert_eq!(
+            err("title_fg = \"red"),
+            "line 1: expected a string or a number"
+        );
+    }
+}
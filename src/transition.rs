@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use crossterm::event;
+use ratatui::{
+    backend::Backend,
+    buffer::{Buffer, Cell},
+    prelude::Rect,
+    style::{Color, Modifier},
+    widgets::Widget,
+    Terminal,
+};
+
+use crate::theme::{blend, rgb};
+
+/// how long moving from one slide to the next takes
+const DURATION: Duration = Duration::from_millis(240);
+const FRAMES: u32 = 12;
+
+/// animation played when moving to the next or previous slide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum Transition {
+    /// push the old slide out to the side
+    Slide,
+    /// fade the old slide out and the new one in
+    Fade,
+}
+
+/// animates from the screen in `from` to the one in `to`, stopping early once a
+/// key or click is waiting so it's handled right away
+pub(crate) fn play<B: Backend>(
+    terminal: &mut Terminal<B>,
+    kind: Transition,
+    from: &Buffer,
+    to: &Buffer,
+    forward: bool,
+) -> Result<()> {
+    if from.area != to.area {
+        return Ok(());
+    }
+    for frame in 1..FRAMES {
+        let progress = frame as f32 / FRAMES as f32;
+        let buffer = match kind {
+            Transition::Slide => slide(from, to, forward, progress),
+            Transition::Fade => fade(from, to, progress),
+        };
+        terminal.draw(|frame| frame.render_widget(Frozen(&buffer), frame.size()))?;
+        if event::poll(DURATION / FRAMES)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// the old slide moves out as the new one moves in, from the right when going forward
+fn slide(from: &Buffer, to: &Buffer, forward: bool, progress: f32) -> Buffer {
+    let area = from.area;
+    let shift = (area.width as f32 * progress).round() as u16;
+    let mut out = Buffer::empty(area);
+    for y in area.top()..area.bottom() {
+        for x in 0..area.width {
+            let cell = if forward {
+                match x.checked_add(shift).filter(|&x| x < area.width) {
+                    Some(src) => from.get(area.x + src, y),
+                    None => to.get(area.x + x + shift - area.width, y),
+                }
+            } else {
+                match x.checked_sub(shift) {
+                    Some(src) => from.get(area.x + src, y),
+                    None => to.get(area.x + x + area.width - shift, y),
+                }
+            };
+            *out.get_mut(area.x + x, y) = cell.clone();
+        }
+    }
+    out
+}
+
+/// the old slide fades away during the first half, the new one shows up in the second
+fn fade(from: &Buffer, to: &Buffer, progress: f32) -> Buffer {
+    let (mut out, amount) = if progress < 0.5 {
+        (from.clone(), progress * 2.0)
+    } else {
+        (to.clone(), (1.0 - progress) * 2.0)
+    };
+    for cell in &mut out.content {
+        fade_cell(cell, amount);
+    }
+    out
+}
+
+/// moves the text color of `cell` towards its background by `amount`
+fn fade_cell(cell: &mut Cell, amount: f32) {
+    const BLACK: [u8; 3] = [0, 0, 0];
+    if cell.fg != Color::Reset && cell.bg != Color::Reset {
+        let [r, g, b] = blend(rgb(cell.fg, BLACK), rgb(cell.bg, BLACK), amount);
+        cell.fg = Color::Rgb(r, g, b);
+        return;
+    }
+    // the terminal's own colors are unknown, dim the text and then hide it
+    if amount > 0.8 {
+        cell.set_symbol(" ");
+    } else if amount > 0.4 {
+        cell.modifier.insert(Modifier::DIM);
+    }
+}
+
+/// draws a buffer that was rendered before
+struct Frozen<'a>(&'a Buffer);
+
+impl Widget for Frozen<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(self.0.area);
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                *buf.get_mut(x, y) = self.0.get(x, y).clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::{path::PathBuf, time::SystemTime};
+
+use anyhow::Result;
+
+use crate::slide::{mkslides, ParseOptions, Slides};
+
+/// rebuilds the deck whenever one of the markdown files' modification time changes,
+/// the included ones as well, polled rather than watched through file system events
+/// so it behaves the same everywhere for one `stat` per file and poll
+pub(crate) struct Watcher {
+    paths: Vec<String>,
+    opts: ParseOptions,
+    /// the files given on the command line followed by the ones they include
+    watched: Vec<PathBuf>,
+    modified: Vec<Option<SystemTime>>,
+}
+
+impl Watcher {
+    pub(crate) fn new(paths: Vec<String>, opts: ParseOptions, slides: &Slides) -> Self {
+        let watched = watched(&paths, slides);
+        let modified = modified(&watched);
+        Self {
+            paths,
+            opts,
+            watched,
+            modified,
+        }
+    }
+
+    /// returns the freshly parsed deck if any file changed since the last call
+    pub(crate) fn poll(&mut self) -> Option<Result<Slides>> {
+        let now = modified(&self.watched);
+        if now == self.modified {
+            return None;
+        }
+        self.modified = now;
+        let reloaded = mkslides(&self.paths, &self.opts);
+        // the edit may have added or dropped includes, a failed one keeps the old set
+        if let Ok(slides) = &reloaded {
+            self.watched = watched(&self.paths, slides);
+            self.modified = modified(&self.watched);
+        }
+        Some(reloaded)
+    }
+}
+
+fn watched(paths: &[String], slides: &Slides) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .map(PathBuf::from)
+        .chain(slides.includes().iter().cloned())
+        .collect()
+}
+
+fn modified(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
@@ -0,0 +1,224 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// greedy word wrap of every line in `src` to at most `width` columns,
+/// words that don't fit on a line of their own are split
+pub(crate) fn wrap(src: &str, width: usize) -> Vec<String> {
+    wrap_styled(&[(src, ())], width)
+        .into_iter()
+        .map(|line| line.into_iter().map(|(text, _)| text).collect())
+        .collect()
+}
+
+/// a line or word made of pieces of text tagged with their style
+type Pieces<S> = Vec<(String, S)>;
+
+/// like [`wrap`] over text made of differently styled pieces, every piece of
+/// the output keeps the style of the input it came from
+pub(crate) fn wrap_styled<S: Copy + PartialEq>(src: &[(&str, S)], width: usize) -> Vec<Pieces<S>> {
+    let mut lines = Lines {
+        width: width.max(1),
+        out: vec![],
+        current: vec![],
+        current_width: 0,
+    };
+    let mut word: Pieces<S> = vec![];
+    // style of the whitespace before `word`, used when joining it to the line
+    let mut space = None;
+    // set once the current source line has any content, like `str::lines`
+    let mut open = false;
+    for &(text, style) in src {
+        for c in text.chars() {
+            if c == '\n' {
+                lines.push_word(&mut word, space.take());
+                lines.break_line();
+                open = false;
+                continue;
+            }
+            open = true;
+            if c.is_whitespace() {
+                lines.push_word(&mut word, space);
+                space = Some(style);
+                continue;
+            }
+            match word.last_mut() {
+                Some((last, s)) if *s == style => last.push(c),
+                _ => word.push((c.into(), style)),
+            }
+        }
+    }
+    lines.push_word(&mut word, space);
+    if open {
+        lines.break_line();
+    }
+    lines.out
+}
+
+struct Lines<S> {
+    width: usize,
+    out: Vec<Pieces<S>>,
+    current: Pieces<S>,
+    current_width: usize,
+}
+
+impl<S: Copy + PartialEq> Lines<S> {
+    fn push_str(&mut self, text: &str, style: S) {
+        match self.current.last_mut() {
+            Some((last, s)) if *s == style => last.push_str(text),
+            _ => self.current.push((text.into(), style)),
+        }
+        self.current_width += text.width();
+    }
+
+    fn break_line(&mut self) {
+        self.out.push(std::mem::take(&mut self.current));
+        self.current_width = 0;
+    }
+
+    /// moves `word` onto the line, starting a new one if it doesn't fit
+    fn push_word(&mut self, word: &mut Pieces<S>, space: Option<S>) {
+        if word.is_empty() {
+            return;
+        }
+        let mut word = std::mem::take(word);
+        let mut word_width = word.iter().map(|(text, _)| text.width()).sum::<usize>();
+        if self.current_width > 0 && self.current_width + 1 + word_width > self.width {
+            self.break_line();
+        }
+        if self.current_width > 0 {
+            self.push_str(" ", space.unwrap_or(word[0].1));
+        }
+        while word_width > self.width - self.current_width {
+            let (head, rest) = split_pieces(word, self.width - self.current_width);
+            if head.is_empty() && self.current_width == 0 {
+                // a single char wider than the whole line, give it a line anyway
+                let (text, style) = rest[0].clone();
+                let end = text.chars().next().map_or(text.len(), char::len_utf8);
+                self.push_str(&text[..end], style);
+                word = split_pieces(rest, text[..end].width()).1;
+                if word.is_empty() {
+                    // the next word breaks the line, or the end of the source does
+                    return;
+                }
+            } else {
+                for (text, style) in &head {
+                    self.push_str(text, *style);
+                }
+                word = rest;
+            }
+            self.break_line();
+            word_width = word.iter().map(|(text, _)| text.width()).sum();
+        }
+        for (text, style) in &word {
+            self.push_str(text, *style);
+        }
+    }
+}
+
+/// splits `pieces` so the first half is at most `width` columns wide
+fn split_pieces<S: Copy>(pieces: Pieces<S>, width: usize) -> (Pieces<S>, Pieces<S>) {
+    let mut head = vec![];
+    let mut rest = vec![];
+    let mut left = width;
+    for (text, style) in pieces {
+        if !rest.is_empty() {
+            rest.push((text, style));
+            continue;
+        }
+        let (h, r) = split_at_width(&text, left);
+        left -= h.width();
+        if !h.is_empty() {
+            head.push((h.to_owned(), style));
+        }
+        if !r.is_empty() {
+            rest.push((r.to_owned(), style));
+        }
+    }
+    (head, rest)
+}
+
+/// splits `src` so the first half is at most `width` columns wide
+fn split_at_width(src: &str, width: usize) -> (&str, &str) {
+    let mut used = 0;
+    for (i, c) in src.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return src.split_at(i);
+        }
+    }
+    (src, "")
+}
+
+/// replaces every tab in `src` with the spaces up to the next multiple of `width`
+/// columns, so indentation doesn't depend on the terminal's tab stops
+pub(crate) fn expand_tabs(src: &str, width: usize) -> String {
+    if !src.contains('\t') {
+        return src.into();
+    }
+    let mut out = String::with_capacity(src.len());
+    push_expanded(&mut out, src, width, 0);
+    out
+}
+
+/// appends `src` to `out` with its tabs expanded as if it started at `column`,
+/// returns the column it ends at
+pub(crate) fn push_expanded(out: &mut String, src: &str, width: usize, mut column: usize) -> usize {
+    let width = width.max(1);
+    for c in src.chars() {
+        match c {
+            '\t' => {
+                let spaces = width - column % width;
+                out.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            }
+            '\n' => {
+                out.push(c);
+                column = 0;
+            }
+            _ => {
+                out.push(c);
+                column += c.width().unwrap_or(0);
+            }
+        }
+    }
+    column
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_words() {
+        assert_eq!(wrap("one two three four", 9), ["one two", "three", "four"]);
+        assert_eq!(wrap("first\n\nsecond\n", 20), ["first", "", "second"]);
+    }
+
+    #[test]
+    fn splits_long_words() {
+        assert_eq!(wrap("abcdefgh", 3), ["abc", "def", "gh"]);
+        // a wide char never gets split, even when the line is narrower
+        assert_eq!(wrap("日本", 1), ["日", "本"]);
+        assert_eq!(wrap("日 本", 1), ["日", "本"]);
+    }
+
+    #[test]
+    fn keeps_styles_across_lines() {
+        let lines = wrap_styled(&[("plain ", 0), ("bold words", 1)], 10);
+        assert_eq!(
+            lines,
+            [
+                // the space keeps the style it had
+                vec![("plain ".to_string(), 0), ("bold".into(), 1)],
+                vec![("words".to_string(), 1)],
+            ]
+        );
+    }
+
+    #[test]
+    fn expands_tabs_to_stops() {
+        assert_eq!(expand_tabs("\tx", 4), "    x");
+        assert_eq!(expand_tabs("ab\tc\n\td", 4), "ab  c\n    d");
+        assert_eq!(expand_tabs("日\tx", 4), "日  x");
+        assert_eq!(expand_tabs("no tabs", 4), "no tabs");
+    }
+}